@@ -1,13 +1,12 @@
 use anyhow::bail;
-use base64::{engine::general_purpose::STANDARD, Engine as _};
 use clap::{Parser, Subcommand};
+use obscuravpn_api::catalog::ExitCatalog;
 use obscuravpn_api::cmd::*;
-use obscuravpn_api::types::{TunnelConfig, WgPubkey};
-use obscuravpn_api::wg_conf::build_wg_conf;
+use obscuravpn_api::types::{AccountId, TunnelConfig, WgSecretKey};
+use obscuravpn_api::versioned::ListExitsVersioned;
+use obscuravpn_api::wg_conf::{build_wg_conf, WgConf, WgConfAudit};
 use obscuravpn_api::Client;
 use qrcode::QrCode;
-use rand::rngs::OsRng;
-use x25519_dalek::{PublicKey, StaticSecret};
 
 #[derive(Parser, Debug, PartialEq)]
 #[command(author, version, about, long_about = None)]
@@ -16,14 +15,18 @@ struct Args {
     #[clap(long, default_value = "https://v1.api.prod.obscura.net/api")]
     base_url: String,
     #[clap(long)]
-    account_no: String,
+    account_no: AccountId,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand, Debug, PartialEq)]
 enum Commands {
-    ListExits,
+    ListExits {
+        /// only show exits matching a city or country
+        #[clap(long)]
+        search: Option<String>,
+    },
     ListTunnels,
     ListRelays,
     CreateObfuscatedTunnel {
@@ -45,6 +48,10 @@ enum Commands {
         #[clap(long)]
         exit: Option<String>,
     },
+    /// check WireGuard configuration files against the existing tunnels
+    AuditWgConfs {
+        files: Vec<std::path::PathBuf>,
+    },
     DeleteAllTunnels,
     TopUp {
         #[clap(long)]
@@ -71,9 +78,10 @@ async fn main() -> anyhow::Result<()> {
             let relays = client.run(ListRelays {}).await?;
             println!("{}", serde_json::to_string_pretty(&relays)?);
         }
-        Commands::ListExits => {
+        Commands::ListExits { search } => {
             eprintln!("Get all exits");
-            let exits = client.run(ListExits2 {}).await?.exits;
+            let catalog = ExitCatalog::from(client.run_versioned(ListExitsVersioned {}).await?);
+            let exits = catalog.search(search.as_deref().unwrap_or_default());
             println!("{:#?}", exits);
         }
         Commands::ListTunnels => {
@@ -83,11 +91,10 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::CreateObfuscatedTunnel { relay, exit } => {
             eprintln!("Creating new tunnel");
-            let sk = StaticSecret::random_from_rng(OsRng);
+            let sk = WgSecretKey::generate();
             eprintln!("Created private key");
-            eprintln!("{}", STANDARD.encode(sk.as_bytes()));
-            let pk = PublicKey::from(&sk);
-            let wg_pubkey = WgPubkey(pk.to_bytes());
+            eprintln!("{sk}");
+            let wg_pubkey = sk.pubkey();
             let tunnel = client
                 .run(CreateTunnel::Obfuscated {
                     id: None,
@@ -102,14 +109,12 @@ async fn main() -> anyhow::Result<()> {
         }
         Commands::CreateStaticTunnel { wg_conf, relay, exit } => {
             eprintln!("Creating new tunnel");
-            let sk = StaticSecret::random_from_rng(OsRng);
-            let pk = PublicKey::from(&sk);
-            let sk = STANDARD.encode(sk.as_bytes());
+            let sk = WgSecretKey::generate();
             if !wg_conf {
                 eprintln!("Created private key");
-                println!("{}", sk);
+                println!("{sk}");
             }
-            let wg_pubkey = WgPubkey(pk.to_bytes());
+            let wg_pubkey = sk.pubkey();
             let tunnel = client
                 .run(CreateTunnel::UdpPort {
                     id: None,
@@ -124,11 +129,40 @@ async fn main() -> anyhow::Result<()> {
                 let TunnelConfig::UdpPort { client, server } = tunnel.config else {
                     bail!("unexpected tunnel variant")
                 };
-                println!("{}", build_wg_conf(Some(tunnel.id), sk, client, server))
+                println!("{}", build_wg_conf(Some(tunnel.id), &sk, client, server)?)
             } else {
                 println!("{}", serde_json::to_string_pretty(&tunnel)?);
             }
         }
+        Commands::AuditWgConfs { files } => {
+            eprintln!("Get all existing tunnels");
+            let tunnels = client.run(ListTunnels {}).await?;
+            for file in files {
+                let conf = match std::fs::read_to_string(&file)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|conf| Ok(conf.parse::<WgConf>()?))
+                {
+                    Ok(conf) => conf,
+                    Err(err) => {
+                        println!("{}: error: {err}", file.display());
+                        continue;
+                    }
+                };
+                let status = match conf.audit(&tunnels) {
+                    WgConfAudit::Current(tunnel) => format!("current ({})", tunnel.id),
+                    WgConfAudit::Stale { tunnel, mismatches } => {
+                        format!(
+                            "stale ({}): {}",
+                            tunnel.id,
+                            mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+                        )
+                    }
+                    WgConfAudit::Deleted => "deleted".to_string(),
+                    WgConfAudit::Untracked => "untracked".to_string(),
+                };
+                println!("{}: {status}", file.display());
+            }
+        }
         Commands::DeleteAllTunnels => {
             eprintln!("Get all existing tunnels");
             let tunnels = client.run(ListTunnels {}).await?;
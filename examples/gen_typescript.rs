@@ -0,0 +1,3 @@
+fn main() {
+    print!("{}", obscuravpn_api::typescript::declarations());
+}
@@ -44,6 +44,24 @@
           '';
 
           rustfmt = craneLib.cargoFmt cargoArgs;
+
+          test-all-features = craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--all-features"; });
+
+          test-no-default-features = craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features"; });
+
+          test-client = craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features --features client"; });
+
+          test-notices = craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features --features notices"; });
+
+          test-probe = craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features --features probe"; });
+
+          test-server = craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features --features server"; });
+
+          test-typescript =
+            craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features --features typescript"; });
+
+          test-unknown-fields =
+            craneLib.cargoTest (cargoArgs // { cargoTestExtraArgs = "--no-default-features --features unknown-fields"; });
         };
 
         devShells.default = pkgs.mkShellNoCC {
@@ -0,0 +1,148 @@
+//! Account status derived from [`AccountInfo`].
+
+use std::time::{Duration, SystemTime};
+
+use crate::types::{AccountInfo, Timestamp};
+
+/// How the account is currently paid for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountStatus {
+    /// A subscription which renews at the end of the current period.
+    Subscribed { renews_at: Timestamp },
+    /// A subscription which was cancelled, but remains active until the end of the current period.
+    SubscriptionCancelled { active_until: Timestamp },
+    /// Prepaid credit from top-ups.
+    Credit { expires_at: Timestamp },
+    /// The account is active without any known payment.
+    Active,
+    /// The account is no longer paid for.
+    Expired {
+        /// When the account ran out of paid time, if known.
+        expired_at: Option<Timestamp>,
+    },
+}
+
+impl AccountStatus {
+    pub fn is_active(&self) -> bool {
+        !matches!(self, AccountStatus::Expired { .. })
+    }
+
+    /// When the account stops being active unless it is paid for, or `None` if it renews automatically or the time is unknown.
+    pub fn expires_at(&self) -> Option<Timestamp> {
+        match self {
+            AccountStatus::SubscriptionCancelled { active_until } => Some(*active_until),
+            AccountStatus::Credit { expires_at } => Some(*expires_at),
+            AccountStatus::Subscribed { .. } | AccountStatus::Active | AccountStatus::Expired { .. } => None,
+        }
+    }
+}
+
+impl AccountInfo {
+    /// The time until which the account is paid, across top-ups and the current subscription period.
+    pub fn paid_through(&self) -> Option<Timestamp> {
+        let credit = self.top_up.as_ref().map(|top_up| top_up.credit_expires_at);
+        let subscription = self
+            .subscription
+            .as_ref()
+            .filter(|subscription| subscription.status.is_paying())
+            .map(|subscription| subscription.current_period_end);
+        credit.max(subscription)
+    }
+
+    /// The account's status, as determined by the server's `active` flag.
+    ///
+    /// The local clock isn't consulted, as it may be skewed. Use [`AccountInfo::is_expiring_soon`] to compare against it.
+    pub fn status(&self) -> AccountStatus {
+        if !self.active {
+            // A canceled or unpaid subscription still tells when its last period ended.
+            let credit = self.top_up.as_ref().map(|top_up| top_up.credit_expires_at);
+            let period_end = self.subscription.as_ref().map(|subscription| subscription.current_period_end);
+            return AccountStatus::Expired {
+                expired_at: credit.max(period_end),
+            };
+        }
+
+        let paid_through = self.paid_through();
+        if let Some(subscription) = self.subscription.as_ref().filter(|subscription| subscription.status.is_paying()) {
+            if !subscription.cancel_at_period_end {
+                return AccountStatus::Subscribed {
+                    renews_at: subscription.current_period_end,
+                };
+            }
+            if Some(subscription.current_period_end) == paid_through {
+                return AccountStatus::SubscriptionCancelled {
+                    active_until: subscription.current_period_end,
+                };
+            }
+        }
+        match paid_through {
+            Some(expires_at) => AccountStatus::Credit { expires_at },
+            None => AccountStatus::Active,
+        }
+    }
+
+    /// Whether the active account stops being active within `threshold` of `now`, without renewing automatically.
+    pub fn is_expiring_soon(&self, now: SystemTime, threshold: Duration) -> bool {
+        self.status()
+            .expires_at()
+            .is_some_and(|expires_at| expires_at.duration_until(now) <= threshold)
+    }
+}
+
+#[test]
+fn test_account_status() {
+    const DAY: u64 = 86400;
+    let now = Timestamp::from_unix_secs(1_700_000_000).to_system_time().unwrap();
+    let at = |days: u64| Timestamp::from(now + Duration::from_secs(days * DAY));
+    let ago = |days: u64| Timestamp::from(now - Duration::from_secs(days * DAY));
+    let week = Duration::from_secs(7 * DAY);
+    let fixture = |json: &str| serde_json::from_str::<AccountInfo>(json).unwrap();
+
+    let credit = fixture(r#"{"id": "1", "active": true, "top_up": {"credit_expires_at": 1700259200}, "subscription": null}"#);
+    assert_eq!(credit.paid_through(), Some(at(3)));
+    assert_eq!(credit.status(), AccountStatus::Credit { expires_at: at(3) });
+    assert!(credit.is_expiring_soon(now, week));
+    assert!(!credit.is_expiring_soon(now, Duration::from_secs(DAY)));
+
+    let subscribed = fixture(
+        r#"{"id": "1", "active": true, "top_up": null, "subscription": {
+            "status": "active", "current_period_start": 1699000000, "current_period_end": 1700432000, "cancel_at_period_end": false
+        }}"#,
+    );
+    assert_eq!(subscribed.status(), AccountStatus::Subscribed { renews_at: at(5) });
+    assert!(!subscribed.is_expiring_soon(now, week));
+
+    let cancelled = fixture(
+        r#"{"id": "1", "active": true, "top_up": {"credit_expires_at": 1699000000}, "subscription": {
+            "status": "active", "current_period_start": 1699000000, "current_period_end": 1700432000, "cancel_at_period_end": true
+        }}"#,
+    );
+    assert_eq!(cancelled.paid_through(), Some(at(5)));
+    assert_eq!(cancelled.status(), AccountStatus::SubscriptionCancelled { active_until: at(5) });
+    assert!(cancelled.is_expiring_soon(now, week));
+
+    let cancelled_with_credit = fixture(
+        r#"{"id": "1", "active": true, "top_up": {"credit_expires_at": 1702592000}, "subscription": {
+            "status": "active", "current_period_start": 1699000000, "current_period_end": 1700432000, "cancel_at_period_end": true
+        }}"#,
+    );
+    assert_eq!(cancelled_with_credit.status(), AccountStatus::Credit { expires_at: at(30) });
+
+    let lapsed_subscription = fixture(
+        r#"{"id": "1", "active": false, "top_up": null, "subscription": {
+            "status": "canceled", "current_period_start": 1690000000, "current_period_end": 1699568000, "cancel_at_period_end": true
+        }}"#,
+    );
+    assert_eq!(lapsed_subscription.paid_through(), None);
+    assert_eq!(lapsed_subscription.status(), AccountStatus::Expired { expired_at: Some(ago(5)) });
+
+    let stale = fixture(r#"{"id": "1", "active": true, "top_up": {"credit_expires_at": 1699827200}, "subscription": null}"#);
+    // The server knows better than a possibly skewed local clock.
+    assert_eq!(stale.status(), AccountStatus::Credit { expires_at: ago(2) });
+    assert!(stale.status().is_active());
+    assert!(stale.is_expiring_soon(now, week));
+
+    let comped = fixture(r#"{"id": "1", "active": true, "top_up": null, "subscription": null}"#);
+    assert_eq!(comped.status(), AccountStatus::Active);
+    assert!(comped.status().is_active());
+}
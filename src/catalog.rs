@@ -0,0 +1,347 @@
+//! Querying the exits returned by [`ListExits2`](crate::cmd::ListExits2).
+//!
+//! [`ExitCatalog`] provides the lookups shared by location pickers: by ID, by country and city, free-text search and a country→city hierarchy.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[cfg(feature = "rand_core")]
+use rand_core::RngCore;
+
+use crate::cmd::ExitList;
+use crate::country::CountryCode;
+use crate::types::OneExit;
+
+/// Selects exits by country and city. Codes are compared case-insensitively and unset fields match any exit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExitFilter {
+    pub country_code: Option<CountryCode>,
+    pub city_code: Option<String>,
+}
+
+impl ExitFilter {
+    pub fn country(country_code: impl Into<CountryCode>) -> Self {
+        Self {
+            country_code: Some(country_code.into()),
+            city_code: None,
+        }
+    }
+
+    pub fn city(country_code: impl Into<CountryCode>, city_code: impl Into<String>) -> Self {
+        Self {
+            country_code: Some(country_code.into()),
+            city_code: Some(city_code.into()),
+        }
+    }
+
+    pub fn matches(&self, exit: &OneExit) -> bool {
+        self.country_code.as_ref().map_or(true, |country_code| *country_code == exit.country_code)
+            && self
+                .city_code
+                .as_ref()
+                .map_or(true, |city_code| city_code.eq_ignore_ascii_case(&exit.city_code))
+    }
+}
+
+/// A country with exits, see [`ExitCatalog::countries`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountryExits<'a> {
+    pub country_code: &'a CountryCode,
+    pub exit_count: usize,
+    pub cities: Vec<CityExits<'a>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CityExits<'a> {
+    pub city_code: &'a str,
+    pub city_name: &'a str,
+    pub exit_count: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ExitCatalog {
+    exits: Vec<OneExit>,
+    by_id: HashMap<String, usize>,
+}
+
+impl ExitCatalog {
+    pub fn new(exits: Vec<OneExit>) -> Self {
+        let by_id = exits.iter().enumerate().map(|(i, exit)| (exit.id.clone(), i)).collect();
+        Self { exits, by_id }
+    }
+
+    /// All exits, in the order they were listed.
+    pub fn exits(&self) -> &[OneExit] {
+        &self.exits
+    }
+
+    pub fn get(&self, id: &str) -> Option<&OneExit> {
+        self.by_id.get(id).map(|&i| &self.exits[i])
+    }
+
+    pub fn filter(&self, filter: &ExitFilter) -> impl Iterator<Item = &OneExit> {
+        let filter = filter.clone();
+        self.exits.iter().filter(move |exit| filter.matches(exit))
+    }
+
+    /// Exits matching `query` against city and country names and codes, best matches first.
+    ///
+    /// Matching ignores case, diacritics and punctuation. Names also match if the query's characters appear in order, so abbreviations like "sfo" find "San Francisco".
+    pub fn search(&self, query: &str) -> Vec<&OneExit> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return self.exits.iter().collect();
+        }
+        let mut matches: Vec<(MatchQuality, &OneExit)> = self.exits.iter().filter_map(|exit| Some((match_exit(&query, exit)?, exit))).collect();
+        matches.sort_by(|(a_quality, a), (b_quality, b)| a_quality.cmp(b_quality).then_with(|| cmp_location(a, b)));
+        matches.into_iter().map(|(_, exit)| exit).collect()
+    }
+
+    /// Exits grouped by country and city, sorted by name.
+    pub fn countries(&self) -> Vec<CountryExits<'_>> {
+        let mut exits: Vec<&OneExit> = self.exits.iter().collect();
+        exits.sort_by(|a, b| cmp_location(a, b));
+
+        let mut countries: Vec<CountryExits> = Vec::new();
+        for exit in exits {
+            let country = match countries.last_mut() {
+                Some(country) if *country.country_code == exit.country_code => country,
+                _ => {
+                    countries.push(CountryExits {
+                        country_code: &exit.country_code,
+                        exit_count: 0,
+                        cities: Vec::new(),
+                    });
+                    countries.last_mut().unwrap()
+                }
+            };
+            country.exit_count += 1;
+            match country.cities.last_mut() {
+                Some(city) if city.city_code == exit.city_code => city.exit_count += 1,
+                _ => country.cities.push(CityExits {
+                    city_code: &exit.city_code,
+                    city_name: &exit.city_name,
+                    exit_count: 1,
+                }),
+            }
+        }
+        countries
+    }
+
+    /// A random exit matching `filter`, using the operating system's random number generator.
+    #[cfg(feature = "rand_core")]
+    pub fn random(&self, filter: &ExitFilter) -> Option<&OneExit> {
+        self.random_with_rng(filter, &mut rand_core::OsRng)
+    }
+
+    #[cfg(feature = "rand_core")]
+    pub fn random_with_rng(&self, filter: &ExitFilter, rng: &mut impl RngCore) -> Option<&OneExit> {
+        let matching: Vec<&OneExit> = self.filter(filter).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        // The modulo bias is negligible for any realistic number of exits.
+        Some(matching[(rng.next_u64() % matching.len() as u64) as usize])
+    }
+}
+
+impl From<Vec<OneExit>> for ExitCatalog {
+    fn from(exits: Vec<OneExit>) -> Self {
+        Self::new(exits)
+    }
+}
+
+impl From<ExitList> for ExitCatalog {
+    fn from(list: ExitList) -> Self {
+        Self::new(list.exits)
+    }
+}
+
+fn cmp_location(a: &OneExit, b: &OneExit) -> Ordering {
+    a.country_code
+        .display_name()
+        .cmp(b.country_code.display_name())
+        .then_with(|| a.country_code.cmp(&b.country_code))
+        .then_with(|| a.city_name.cmp(&b.city_name))
+        .then_with(|| a.city_code.cmp(&b.city_code))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// How well a query matches, best first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchQuality {
+    Exact,
+    Prefix,
+    WordPrefix,
+    Substring,
+    Subsequence,
+}
+
+fn match_exit(query: &str, exit: &OneExit) -> Option<MatchQuality> {
+    let codes = [exit.country_code.as_str(), &exit.city_code]
+        .into_iter()
+        .filter(|code| normalize(code) == query)
+        .map(|_| MatchQuality::Exact);
+    let names = [Some(exit.city_name.as_str()), exit.country_code.name()]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| match_name(query, &normalize(name)));
+    codes.chain(names).min()
+}
+
+fn match_name(query: &str, name: &str) -> Option<MatchQuality> {
+    if name == query {
+        Some(MatchQuality::Exact)
+    } else if name.starts_with(query) {
+        Some(MatchQuality::Prefix)
+    } else if name.split(' ').any(|word| word.starts_with(query)) {
+        Some(MatchQuality::WordPrefix)
+    } else if name.contains(query) {
+        Some(MatchQuality::Substring)
+    } else if query.len() >= 3 && is_subsequence(query, name) {
+        Some(MatchQuality::Subsequence)
+    } else {
+        None
+    }
+}
+
+fn is_subsequence(query: &str, name: &str) -> bool {
+    let mut name = name.chars();
+    query.chars().filter(|c| *c != ' ').all(|c| name.any(|n| n == c))
+}
+
+/// Lowercases, strips diacritics from Latin letters and collapses everything but letters and digits into single spaces.
+fn normalize(s: &str) -> String {
+    let mut normalized = String::with_capacity(s.len());
+    for c in s.chars().flat_map(char::to_lowercase).map(strip_diacritic) {
+        if c.is_alphanumeric() {
+            normalized.push(c);
+        } else if !normalized.is_empty() && !normalized.ends_with(' ') {
+            normalized.push(' ');
+        }
+    }
+    normalized.truncate(normalized.trim_end().len());
+    normalized
+}
+
+fn strip_diacritic(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'ğ' => 'g',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => 'i',
+        'ł' => 'l',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => 'o',
+        'ř' => 'r',
+        'ś' | 'š' | 'ş' | 'ș' => 's',
+        'ť' | 'ţ' | 'ț' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+fn test_catalog() -> ExitCatalog {
+    use crate::test_support::exit;
+
+    ExitCatalog::new(vec![
+        exit("nyc-1", "US", "nyc", "New York"),
+        exit("sfo-1", "US", "sfo", "San Francisco"),
+        exit("nyc-2", "US", "nyc", "New York"),
+        exit("zrh-1", "CH", "zrh", "Zürich"),
+        exit("yul-1", "CA", "yul", "Montréal"),
+        exit("ams-1", "NL", "ams", "Amsterdam"),
+    ])
+}
+
+#[test]
+fn test_lookup_and_filter() {
+    let catalog = test_catalog();
+    assert_eq!(catalog.get("zrh-1").unwrap().city_name, "Zürich");
+    assert!(catalog.get("nope").is_none());
+
+    let ids = |filter: &ExitFilter| catalog.filter(filter).map(|exit| exit.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids(&ExitFilter::country("us")), ["nyc-1", "sfo-1", "nyc-2"]);
+    assert_eq!(ids(&ExitFilter::city("US", "NYC")), ["nyc-1", "nyc-2"]);
+    assert_eq!(ids(&ExitFilter::default()).len(), 6);
+    assert!(ids(&ExitFilter::city("CH", "nyc")).is_empty());
+}
+
+#[test]
+fn test_search() {
+    let catalog = test_catalog();
+    let search = |query: &str| catalog.search(query).into_iter().map(|exit| exit.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(search("zurich"), ["zrh-1"]);
+    assert_eq!(search("MONTREAL"), ["yul-1"]);
+    assert_eq!(search("switz"), ["zrh-1"]);
+    assert_eq!(search("york"), ["nyc-1", "nyc-2"]);
+    assert_eq!(search("united states"), ["nyc-1", "nyc-2", "sfo-1"]);
+    assert_eq!(search("sfo"), ["sfo-1"]);
+    assert_eq!(search("nl"), ["ams-1"]);
+    assert_eq!(search("snfrn"), ["sfo-1"]);
+    assert!(search("atlantis").is_empty());
+    assert_eq!(search("  ").len(), 6);
+
+    // Exact and prefix matches rank above substring matches.
+    assert_eq!(search("am")[0], "ams-1");
+}
+
+#[test]
+fn test_countries() {
+    let catalog = test_catalog();
+    let countries = catalog.countries();
+    let names: Vec<_> = countries.iter().map(|country| country.country_code.name().unwrap()).collect();
+    assert_eq!(names, ["Canada", "Netherlands", "Switzerland", "United States"]);
+    let us = &countries[3];
+    assert_eq!(us.exit_count, 3);
+    assert_eq!(
+        us.cities,
+        [
+            CityExits {
+                city_code: "nyc",
+                city_name: "New York",
+                exit_count: 2
+            },
+            CityExits {
+                city_code: "sfo",
+                city_name: "San Francisco",
+                exit_count: 1
+            },
+        ]
+    );
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn test_random() {
+    struct Counter(u64);
+    impl RngCore for Counter {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+        fn next_u64(&mut self) -> u64 {
+            self.0 += 1;
+            self.0
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    let catalog = test_catalog();
+    let filter = ExitFilter::city("US", "nyc");
+    let mut rng = Counter(0);
+    let picks: Vec<_> = (0..4).map(|_| catalog.random_with_rng(&filter, &mut rng).unwrap().id.as_str()).collect();
+    assert_eq!(picks, ["nyc-2", "nyc-1", "nyc-2", "nyc-1"]);
+    assert!(catalog.random(&ExitFilter::country("CH")).is_some_and(|exit| exit.id == "zrh-1"));
+    assert!(catalog.random(&ExitFilter::country("DE")).is_none());
+}
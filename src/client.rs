@@ -1,19 +1,28 @@
-use crate::cmd::{parse_response, ApiError, ApiErrorKind, Cmd, ProtocolError};
+use crate::cmd::{parse_response, ApiError, ApiErrorBody, ApiErrorKind, Cmd, ProtocolError, Remediation, ResponseError};
+use crate::l10n::AcceptLanguage;
 use crate::token::AcquireToken;
-use crate::types::AuthToken;
+use crate::types::{AccountId, AuthToken};
+use crate::versioned::VersionedCmd;
 use anyhow::{anyhow, Context};
+use std::any::TypeId;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+/// How long a revision negotiated by [`Client::run_versioned`] is used before newer revisions are tried again.
+const VERSION_TTL: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Debug)]
 pub struct Client {
-    account_id: String,
+    account_id: AccountId,
     base_url: String,
     http: reqwest::Client,
     cached_auth_token: Arc<Mutex<Option<AuthToken>>>,
     acquiring_auth_token: tokio::sync::Mutex<()>,
+    negotiated_versions: Mutex<HashMap<TypeId, (usize, Instant)>>,
+    accept_language: Mutex<Option<AcceptLanguage>>,
 }
 
 #[derive(Error, Debug)]
@@ -29,8 +38,46 @@ pub enum ClientError {
     Other(#[from] anyhow::Error),
 }
 
+impl ClientError {
+    /// Whether repeating the same request later may succeed without any user action.
+    ///
+    /// Of the other errors, only timeouts and failures to connect are retryable. The rest, such as failing to build the request, would fail again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::ApiError(err) => err.is_retryable(),
+            ClientError::ProtocolError(err) => err.is_retryable(),
+            ClientError::Other(err) => is_network_error(err),
+        }
+    }
+
+    pub fn remediation(&self) -> Remediation {
+        match self {
+            ClientError::ApiError(err) => err.remediation(),
+            ClientError::ProtocolError(err) => err.remediation(),
+            ClientError::Other(err) if is_network_error(err) => Remediation::CheckConnection,
+            ClientError::Other(_) => Remediation::ContactSupport,
+        }
+    }
+}
+
+/// Whether `err` was caused by a timeout or a failure to connect.
+fn is_network_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|err| err.is_timeout() || err.is_connect())
+}
+
+impl From<ResponseError> for ClientError {
+    fn from(err: ResponseError) -> Self {
+        match err {
+            ResponseError::ApiError(err) => err.into(),
+            ResponseError::ProtocolError(err) => err.into(),
+        }
+    }
+}
+
 impl Client {
-    pub fn new(base_url: impl ToString, account_id: String, user_agent: &str) -> anyhow::Result<Self> {
+    pub fn new(base_url: impl ToString, account_id: AccountId, user_agent: &str) -> anyhow::Result<Self> {
         let mut base_url = base_url.to_string();
         if !base_url.ends_with('/') {
             base_url += "/"
@@ -46,6 +93,8 @@ impl Client {
                 .build()
                 .context("failed to initialize HTTP client")?,
             acquiring_auth_token: tokio::sync::Mutex::new(()),
+            negotiated_versions: Mutex::new(HashMap::new()),
+            accept_language: Mutex::new(None),
         })
     }
 
@@ -85,7 +134,15 @@ impl Client {
         *self.cached_auth_token.lock().unwrap() = token
     }
 
-    async fn send_http(&self, request: http::Request<String>) -> anyhow::Result<reqwest::Response> {
+    /// Sets the languages sent in the `Accept-Language` header of subsequent requests.
+    pub fn set_accept_language(&self, accept_language: Option<AcceptLanguage>) {
+        *self.accept_language.lock().unwrap() = accept_language
+    }
+
+    async fn send_http(&self, mut request: http::Request<String>) -> anyhow::Result<reqwest::Response> {
+        if let Some(accept_language) = self.accept_language.lock().unwrap().as_ref().and_then(|a| a.to_header_value()) {
+            request.headers_mut().insert(http::header::ACCEPT_LANGUAGE, accept_language);
+        }
         let request = request.try_into().context("could not construct reqwest::Request")?;
         self.http.execute(request).await.context("error executing request")
     }
@@ -102,6 +159,42 @@ impl Client {
         Err(anyhow!("repeatedly acquired invalid auth token").into())
     }
 
+    /// Runs the newest revision of `cmd` supported by the server.
+    ///
+    /// Falls back to older revisions when the server responds with `NoApiRoute`. The negotiated revision is remembered for subsequent calls, for an hour or until the server stops accepting it, so that upgraded servers are noticed.
+    pub async fn run_versioned<V: VersionedCmd>(&self, cmd: V) -> Result<V::Output, ClientError> {
+        let key = TypeId::of::<V>();
+        let cached = self.negotiated_versions.lock().unwrap().get(&key).copied();
+        let cached = cached.filter(|(_, negotiated_at)| negotiated_at.elapsed() < VERSION_TTL);
+        let mut negotiating = cached.is_none();
+        let mut version = cached.map_or(0, |(version, _)| version);
+        loop {
+            match cmd.run_version(self, version).await {
+                Ok(output) => {
+                    self.negotiated_versions.lock().unwrap().insert(key, (version, Instant::now()));
+                    return Ok(output);
+                }
+                Err(ClientError::ApiError(ApiError {
+                    body: ApiErrorBody {
+                        error: ApiErrorKind::NoApiRoute {},
+                        ..
+                    },
+                    ..
+                })) if !negotiating || version + 1 < V::VERSIONS => {
+                    if negotiating {
+                        version += 1;
+                    } else {
+                        // The server dropped the remembered revision, start over from the newest.
+                        self.negotiated_versions.lock().unwrap().remove(&key);
+                        negotiating = true;
+                        version = 0;
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
     // Sends the http request and maps expected error codes to client errors.
     // Returns `Ok(None)` if the auth token is invalid, because this error shouldn't bubble up.
     async fn try_run<C: Cmd>(&self, body: &C, auth_token: &AuthToken) -> Result<Option<C::Output>, ClientError> {
@@ -117,3 +210,20 @@ impl Client {
         }
     }
 }
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_error_classification() {
+    use anyhow::Context as _;
+
+    // Nothing listens on the discard port of localhost.
+    let connect_err = reqwest::get("http://127.0.0.1:9/").await.unwrap_err();
+    let err = ClientError::from(anyhow::Error::new(connect_err).context("error sending request"));
+    assert!(err.is_retryable());
+    assert_eq!(err.remediation(), Remediation::CheckConnection);
+
+    let build_err = serde_json::from_str::<u32>("x").context("error serializing request").unwrap_err();
+    let err = ClientError::from(build_err);
+    assert!(!err.is_retryable());
+    assert_eq!(err.remediation(), Remediation::ContactSupport);
+}
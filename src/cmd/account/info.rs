@@ -3,6 +3,7 @@ use crate::types::AccountInfo;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct GetAccountInfo();
 
 impl Cmd for GetAccountInfo {
@@ -23,3 +24,36 @@ fn test_account_info_json() {
     "#;
     crate::cmd::check_cmd_json::<GetAccountInfo>(None, Some(output_json));
 }
+
+#[test]
+fn test_account_info_unknown_fields_json() {
+    let output_json = r#"
+        {
+          "id": "0000000000000000000",
+          "active": true,
+          "top_up": { "credit_expires_at": 1000, "future_top_up_field": "a" },
+          "subscription": {
+            "status": "active",
+            "current_period_start": 1000,
+            "current_period_end": 2000,
+            "cancel_at_period_end": false,
+            "future_subscription_field": { "nested": [1, 2] }
+          },
+          "future_account_field": 7
+        }
+    "#;
+    let known_output_json = r#"
+        {
+          "id": "0000000000000000000",
+          "active": true,
+          "top_up": { "credit_expires_at": 1000 },
+          "subscription": {
+            "status": "active",
+            "current_period_start": 1000,
+            "current_period_end": 2000,
+            "cancel_at_period_end": false
+          }
+        }
+    "#;
+    crate::cmd::check_cmd_unknown_fields_json::<GetAccountInfo>(output_json, known_output_json);
+}
@@ -7,6 +7,7 @@ use super::Cmd;
 const EXITS_PATH: &str = "exits";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ListExits {}
 
 impl Cmd for ListExits {
@@ -1,19 +1,36 @@
 use serde::{Deserialize, Serialize};
 
-use crate::types::OneExit;
+use crate::types::{OneExit, UnknownFields};
 
 use super::Cmd;
 
 const EXITS_PATH: &str = "exits2";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ListExits2 {}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ExitList {
     pub exits: Vec<OneExit>,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl ExitList {
+    pub fn new(exits: Vec<OneExit>) -> Self {
+        Self {
+            exits,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
+crate::types::unknown_fields!(ExitList);
+
 impl Cmd for ListExits2 {
     type Output = ExitList;
     const METHOD: http::Method = http::Method::GET;
@@ -34,3 +51,30 @@ fn test_json() {
     "#;
     crate::cmd::check_cmd_json::<ListExits2>(None, Some(output_json));
 }
+
+#[test]
+fn test_unknown_fields_json() {
+    let output_json = r#"
+    {
+      "exits": [{
+        "id": "NYC-001",
+        "country_code": "US",
+        "city_code": "nyc",
+        "city_name": "New York",
+        "future_exit_field": null
+      }],
+      "future_list_field": "x"
+    }
+    "#;
+    let known_output_json = r#"
+    {
+      "exits": [{
+        "id": "NYC-001",
+        "country_code": "US",
+        "city_code": "nyc",
+        "city_name": "New York"
+      }]
+    }
+    "#;
+    crate::cmd::check_cmd_unknown_fields_json::<ListExits2>(output_json, known_output_json);
+}
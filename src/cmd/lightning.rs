@@ -4,11 +4,13 @@ use serde::{Deserialize, Serialize};
 const LIGHTNING_TOP_UP_PATH: &str = "lightning/top_up";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct LightningTopUpInfo {
     pub invoice: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CreateLightningTopUp {
     pub months: u16,
 }
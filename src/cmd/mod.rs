@@ -17,11 +17,15 @@ use std::any::Any;
 pub use stripe::*;
 pub use tunnel::*;
 
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(feature = "client")]
+use anyhow::Context as _;
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use thiserror::Error;
 use url::Url;
 
-use crate::types::AuthToken;
+use crate::types::{AuthToken, Timestamp};
+#[cfg(feature = "client")]
 use crate::ClientError;
 
 pub trait Cmd: Serialize + DeserializeOwned + std::fmt::Debug {
@@ -49,35 +53,228 @@ pub trait Cmd: Serialize + DeserializeOwned + std::fmt::Debug {
 pub struct ApiError {
     pub status: http::StatusCode,
     pub body: ApiErrorBody,
+    /// The `Content-Language` of the response, which is the language of `body.msg`.
+    pub content_language: Option<String>,
+}
+
+impl ApiError {
+    /// Creates an error response. The API doesn't define a status code per error kind, so the handler chooses it.
+    pub fn new(status: http::StatusCode, error: ApiErrorKind, msg: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: ApiErrorBody {
+                error,
+                msg: msg.into(),
+                detail: None,
+            },
+            content_language: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.body.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_content_language(mut self, language: impl Into<String>) -> Self {
+        self.content_language = Some(language.into());
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ApiErrorBody {
     pub error: ApiErrorKind,
     pub msg: String,
 
     /// Debugging information, not intended for end-users.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "typescript", ts(optional))]
     pub detail: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub enum ApiErrorKind {
-    AccountExpired {},
+    AccountExpired {
+        /// When the account expired.
+        #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "lenient")]
+        #[cfg_attr(feature = "typescript", ts(optional))]
+        expired_at: Option<Timestamp>,
+    },
     BadRequest {},
     InternalError {},
     MissingOrInvalidAuthToken {},
     NoApiRoute {},
     NoLongerSupported {},
     NoMatchingExit {},
-    RateLimitExceeded {},
-    SignupLimitExceeded {},
-    TunnelLimitExceeded {},
+    RateLimitExceeded {
+        /// When requests will be accepted again.
+        #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "lenient")]
+        #[cfg_attr(feature = "typescript", ts(optional))]
+        reset_at: Option<Timestamp>,
+    },
+    SignupLimitExceeded {
+        /// When signups will be accepted again.
+        #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "lenient")]
+        #[cfg_attr(feature = "typescript", ts(optional))]
+        reset_at: Option<Timestamp>,
+    },
+    TunnelLimitExceeded {
+        /// Number of tunnels the account currently has.
+        #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "lenient")]
+        #[cfg_attr(feature = "typescript", ts(optional, type = "number"))]
+        tunnel_count: Option<u32>,
+        /// Maximum number of tunnels the account may have.
+        #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "lenient")]
+        #[cfg_attr(feature = "typescript", ts(optional, type = "number"))]
+        tunnel_limit: Option<u32>,
+    },
 
     #[serde(untagged)]
     Unknown(serde_json::Value),
 }
 
+impl ApiErrorKind {
+    /// The name of the variant as sent by the server, or `None` if unknown.
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self {
+            ApiErrorKind::AccountExpired { .. } => "AccountExpired",
+            ApiErrorKind::BadRequest {} => "BadRequest",
+            ApiErrorKind::InternalError {} => "InternalError",
+            ApiErrorKind::MissingOrInvalidAuthToken {} => "MissingOrInvalidAuthToken",
+            ApiErrorKind::NoApiRoute {} => "NoApiRoute",
+            ApiErrorKind::NoLongerSupported {} => "NoLongerSupported",
+            ApiErrorKind::NoMatchingExit {} => "NoMatchingExit",
+            ApiErrorKind::RateLimitExceeded { .. } => "RateLimitExceeded",
+            ApiErrorKind::SignupLimitExceeded { .. } => "SignupLimitExceeded",
+            ApiErrorKind::TunnelLimitExceeded { .. } => "TunnelLimitExceeded",
+            ApiErrorKind::Unknown(_) => return None,
+        })
+    }
+
+    /// Whether repeating the same request later may succeed without any user action.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiErrorKind::InternalError {}
+            | ApiErrorKind::MissingOrInvalidAuthToken {}
+            | ApiErrorKind::RateLimitExceeded { .. }
+            | ApiErrorKind::SignupLimitExceeded { .. } => true,
+            ApiErrorKind::AccountExpired { .. }
+            | ApiErrorKind::BadRequest {}
+            | ApiErrorKind::NoApiRoute {}
+            | ApiErrorKind::NoLongerSupported {}
+            | ApiErrorKind::NoMatchingExit {}
+            | ApiErrorKind::TunnelLimitExceeded { .. }
+            | ApiErrorKind::Unknown(_) => false,
+        }
+    }
+
+    pub fn remediation(&self) -> Remediation {
+        match self {
+            ApiErrorKind::AccountExpired { .. } => Remediation::Pay,
+            ApiErrorKind::BadRequest {} => Remediation::ContactSupport,
+            ApiErrorKind::InternalError {} => Remediation::TryLater { at: None },
+            ApiErrorKind::MissingOrInvalidAuthToken {} => Remediation::TryLater { at: None },
+            ApiErrorKind::NoApiRoute {} => Remediation::Update,
+            ApiErrorKind::NoLongerSupported {} => Remediation::Update,
+            ApiErrorKind::NoMatchingExit {} => Remediation::ChooseDifferentExit,
+            ApiErrorKind::RateLimitExceeded { reset_at } => Remediation::TryLater { at: *reset_at },
+            ApiErrorKind::SignupLimitExceeded { reset_at } => Remediation::TryLater { at: *reset_at },
+            ApiErrorKind::TunnelLimitExceeded { .. } => Remediation::DeleteTunnel,
+            ApiErrorKind::Unknown(_) => Remediation::ContactSupport,
+        }
+    }
+}
+
+/// What the user can do to resolve an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Remediation {
+    /// The account has no paid time left.
+    Pay,
+    /// This version of the app is no longer compatible with the API.
+    Update,
+    /// The request may succeed later.
+    TryLater {
+        /// The earliest time to try again, if known.
+        at: Option<Timestamp>,
+    },
+    /// The account has no free tunnel slots.
+    DeleteTunnel,
+    /// The requested location isn't available.
+    ChooseDifferentExit,
+    /// The network intercepted the request, most likely with a captive portal.
+    SignInToNetwork,
+    /// The API couldn't be reached.
+    CheckConnection,
+    /// Nothing the user can do, other than reporting the problem.
+    ContactSupport,
+}
+
+impl ApiError {
+    pub fn is_retryable(&self) -> bool {
+        self.body.error.is_retryable()
+    }
+
+    pub fn remediation(&self) -> Remediation {
+        self.body.error.remediation()
+    }
+}
+
+impl ProtocolError {
+    /// Whether the response looks like an HTML page, which is typical for captive portals.
+    pub fn is_html(&self) -> bool {
+        let start = self.raw.trim_start().get(..64).unwrap_or(self.raw.trim_start());
+        start.starts_with('<') && start.to_ascii_lowercase().contains("html")
+    }
+
+    /// Proxies and load balancers in front of the API respond with these statuses on transient failures.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.status,
+            http::StatusCode::BAD_GATEWAY | http::StatusCode::SERVICE_UNAVAILABLE | http::StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    pub fn remediation(&self) -> Remediation {
+        if self.is_html() {
+            Remediation::SignInToNetwork
+        } else if self.is_retryable() {
+            Remediation::TryLater { at: None }
+        } else {
+            Remediation::ContactSupport
+        }
+    }
+}
+
+impl ResponseError {
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ResponseError::ApiError(err) => err.is_retryable(),
+            ResponseError::ProtocolError(err) => err.is_retryable(),
+        }
+    }
+
+    pub fn remediation(&self) -> Remediation {
+        match self {
+            ResponseError::ApiError(err) => err.remediation(),
+            ResponseError::ProtocolError(err) => err.remediation(),
+        }
+    }
+}
+
+// Error payloads are informational, so a field which changed type is treated as missing instead of failing to parse the error.
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
 #[derive(Error, Debug)]
 #[error("Unexpected API response: {source}")]
 pub struct ProtocolError {
@@ -86,45 +283,72 @@ pub struct ProtocolError {
     pub source: anyhow::Error,
 }
 
-pub async fn parse_response<T: 'static + DeserializeOwned>(res: reqwest::Response) -> Result<T, ClientError> {
-    let is_json = res
+/// A response that couldn't be decoded into the expected output.
+#[derive(Error, Debug)]
+pub enum ResponseError {
+    #[error("API Error: {0}")]
+    ApiError(#[from] ApiError),
+    #[error("Protocol Error: {0}")]
+    ProtocolError(#[from] ProtocolError),
+}
+
+fn is_json_content_type(content_type: &http::HeaderValue) -> bool {
+    let Ok(content_type) = content_type.to_str() else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    essence.eq_ignore_ascii_case("application/json")
+}
+
+/// Decodes an API response without performing any IO.
+///
+/// Non-JSON responses are classified as [`ProtocolError`]s, as are JSON bodies which don't match the expected shape. JSON responses with a non-success status are [`ApiError`]s.
+pub fn decode_response<T: 'static + DeserializeOwned>(res: http::Response<Bytes>) -> Result<T, ResponseError> {
+    let status = res.status();
+    let is_json = res.headers().get(http::header::CONTENT_TYPE).is_some_and(is_json_content_type);
+    let content_language = res
         .headers()
-        .get(http::header::CONTENT_TYPE)
-        .is_some_and(|h| h.as_bytes() == b"application/json");
+        .get(http::header::CONTENT_LANGUAGE)
+        .and_then(|language| language.to_str().ok())
+        .map(str::to_string);
+    let body = res.into_body();
+    let protocol_error = |source: anyhow::Error| ProtocolError {
+        status,
+        raw: String::from_utf8_lossy(&body).into_owned(),
+        source,
+    };
+
     if !is_json {
-        let status = res.status();
-        return match res.text().await {
-            Ok(raw) => Err(ClientError::ProtocolError(ProtocolError {
-                status,
-                raw,
-                source: anyhow::anyhow!("Non-JSON {status} response"),
-            })),
-            Err(err) => Err(ClientError::ProtocolError(ProtocolError {
-                status,
-                raw: String::new(),
-                source: err.into(),
-            })),
-        };
+        return Err(protocol_error(anyhow::anyhow!("Non-JSON {status} response")).into());
     }
 
-    let status = res.status();
     if !status.is_success() {
-        return Err(ClientError::ApiError(ApiError {
-            status,
-            body: res.json().await.map_err(|err| {
-                ClientError::ProtocolError(ProtocolError {
-                    status,
-                    raw: String::new(),
-                    source: err.into(),
-                })
-            })?,
-        }));
+        return match serde_json::from_slice(&body) {
+            Ok(body) => Err(ApiError {
+                status,
+                body,
+                content_language,
+            }
+            .into()),
+            Err(err) => Err(protocol_error(err.into()).into()),
+        };
     }
     let empty: Box<dyn Any> = Box::new(());
     if let Ok(empty) = empty.downcast::<T>() {
         return Ok(*empty);
     }
-    Ok(res.json().await.map_err(anyhow::Error::new)?)
+    serde_json::from_slice(&body).map_err(|err| protocol_error(err.into()).into())
+}
+
+#[cfg(feature = "client")]
+pub async fn parse_response<T: 'static + DeserializeOwned>(res: reqwest::Response) -> Result<T, ClientError> {
+    let status = res.status();
+    let headers = res.headers().clone();
+    let body = res.bytes().await.context("error reading response body")?;
+    let mut res = http::Response::new(body);
+    *res.status_mut() = status;
+    *res.headers_mut() = headers;
+    Ok(decode_response(res)?)
 }
 
 #[cfg(test)]
@@ -151,11 +375,25 @@ where
     }
 }
 
+/// Checks that fields unknown to this crate round-trip with the `unknown-fields` feature.
+///
+/// Without the feature they must be dropped, which results in `known_output_json`.
+#[cfg(test)]
+pub(crate) fn check_cmd_unknown_fields_json<T: Cmd>(output_json: &str, known_output_json: &str) {
+    if cfg!(feature = "unknown-fields") {
+        check_cmd_json::<T>(None, Some(output_json));
+    } else {
+        let output: T::Output = serde_json::from_str(output_json).unwrap();
+        let known_output_json: serde_json::Value = serde_json::from_str(known_output_json).unwrap();
+        assert_eq!(known_output_json, serde_json::to_value(output).unwrap());
+    }
+}
+
 #[test]
 fn check_err_json() {
     assert_eq!(
         serde_json::to_string(&ApiErrorBody {
-            error: ApiErrorKind::AccountExpired {},
+            error: ApiErrorKind::AccountExpired { expired_at: None },
             msg: "Account Expired".into(),
             detail: None,
         })
@@ -175,7 +413,7 @@ fn check_err_json() {
         )
         .unwrap(),
         ApiErrorBody {
-            error: ApiErrorKind::AccountExpired {},
+            error: ApiErrorKind::AccountExpired { expired_at: None },
             msg: "Account Expired".into(),
             detail: None,
         }
@@ -235,3 +473,128 @@ fn check_err_json() {
         }
     );
 }
+
+#[test]
+fn check_err_payload_json() {
+    assert_eq!(
+        serde_json::from_str::<ApiErrorKind>(r#"{"TunnelLimitExceeded": {"tunnel_count": 5, "tunnel_limit": 5, "future_field": 1}}"#).unwrap(),
+        ApiErrorKind::TunnelLimitExceeded {
+            tunnel_count: Some(5),
+            tunnel_limit: Some(5),
+        }
+    );
+    assert_eq!(
+        serde_json::to_string(&ApiErrorKind::RateLimitExceeded {
+            reset_at: Some(Timestamp::from_unix_secs(1725050273))
+        })
+        .unwrap(),
+        r#"{"RateLimitExceeded":{"reset_at":1725050273}}"#,
+    );
+
+    // Payload fields with an unexpected type are ignored rather than turning the error into `Unknown`.
+    assert_eq!(
+        serde_json::from_str::<ApiErrorKind>(r#"{"AccountExpired": {"expired_at": "yesterday"}}"#).unwrap(),
+        ApiErrorKind::AccountExpired { expired_at: None }
+    );
+    assert_eq!(
+        serde_json::from_str::<ApiErrorKind>(r#"{"SignupLimitExceeded": {"reset_at": null}}"#).unwrap(),
+        ApiErrorKind::SignupLimitExceeded { reset_at: None }
+    );
+}
+
+#[test]
+fn test_remediation() {
+    let api_error = |error: ApiErrorKind| ApiError::new(http::StatusCode::BAD_REQUEST, error, "");
+    assert_eq!(
+        api_error(ApiErrorKind::AccountExpired { expired_at: None }).remediation(),
+        Remediation::Pay
+    );
+    assert_eq!(api_error(ApiErrorKind::NoLongerSupported {}).remediation(), Remediation::Update);
+    assert_eq!(
+        api_error(ApiErrorKind::SignupLimitExceeded {
+            reset_at: Some(Timestamp::from_unix_secs(1000))
+        })
+        .remediation(),
+        Remediation::TryLater {
+            at: Some(Timestamp::from_unix_secs(1000))
+        }
+    );
+    assert!(api_error(ApiErrorKind::RateLimitExceeded { reset_at: None }).is_retryable());
+    assert!(!api_error(ApiErrorKind::TunnelLimitExceeded {
+        tunnel_count: None,
+        tunnel_limit: None
+    })
+    .is_retryable());
+    assert_eq!(
+        api_error(ApiErrorKind::Unknown("Other".into())).remediation(),
+        Remediation::ContactSupport
+    );
+
+    let protocol_error = |status: http::StatusCode, raw: &str| ProtocolError {
+        status,
+        raw: raw.into(),
+        source: anyhow::anyhow!("test"),
+    };
+    let portal = protocol_error(http::StatusCode::OK, "\n<!DOCTYPE html><html><body>Sign in to Airport WiFi</body></html>");
+    assert!(portal.is_html());
+    assert_eq!(portal.remediation(), Remediation::SignInToNetwork);
+    assert!(!portal.is_retryable());
+    let gateway = protocol_error(http::StatusCode::BAD_GATEWAY, "Bad Gateway");
+    assert!(gateway.is_retryable());
+    assert_eq!(gateway.remediation(), Remediation::TryLater { at: None });
+    assert_eq!(protocol_error(http::StatusCode::OK, "{}").remediation(), Remediation::ContactSupport);
+}
+
+#[test]
+fn test_decode_response() {
+    fn response(status: http::StatusCode, content_type: &str, body: &'static str) -> http::Response<Bytes> {
+        http::Response::builder()
+            .status(status)
+            .header(http::header::CONTENT_TYPE, content_type)
+            .body(Bytes::from_static(body.as_bytes()))
+            .unwrap()
+    }
+
+    let output: ExitList = decode_response(response(http::StatusCode::OK, "application/json; charset=utf-8", r#"{"exits":[]}"#)).unwrap();
+    assert_eq!(output.exits, vec![]);
+    decode_response::<()>(response(http::StatusCode::OK, "Application/JSON", "")).unwrap();
+
+    match decode_response::<ExitList>(response(http::StatusCode::OK, "text/html", "<html></html>")) {
+        Err(ResponseError::ProtocolError(err)) => {
+            assert_eq!(err.status, http::StatusCode::OK);
+            assert_eq!(err.raw, "<html></html>");
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    match decode_response::<ExitList>(response(http::StatusCode::OK, "application/json", r#"{"unexpected":true}"#)) {
+        Err(ResponseError::ProtocolError(err)) => assert_eq!(err.raw, r#"{"unexpected":true}"#),
+        res => panic!("unexpected result: {res:?}"),
+    }
+    match decode_response::<ExitList>(response(
+        http::StatusCode::TOO_MANY_REQUESTS,
+        "application/json;charset=utf-8",
+        r#"{"error":{"RateLimitExceeded":{}},"msg":"Slow down"}"#,
+    )) {
+        Err(ResponseError::ApiError(err)) => {
+            assert_eq!(err.status, http::StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(err.body.error, ApiErrorKind::RateLimitExceeded { reset_at: None });
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+    let mut translated = response(
+        http::StatusCode::NOT_FOUND,
+        "application/json",
+        r#"{"error":{"NoMatchingExit":{}},"msg":"Kein Server"}"#,
+    );
+    translated
+        .headers_mut()
+        .insert(http::header::CONTENT_LANGUAGE, http::HeaderValue::from_static("de"));
+    match decode_response::<ExitList>(translated) {
+        Err(ResponseError::ApiError(err)) => assert_eq!(err.content_language.as_deref(), Some("de")),
+        res => panic!("unexpected result: {res:?}"),
+    }
+    match decode_response::<()>(response(http::StatusCode::BAD_GATEWAY, "application/json", "Bad Gateway")) {
+        Err(ResponseError::ProtocolError(err)) => assert_eq!(err.status, http::StatusCode::BAD_GATEWAY),
+        res => panic!("unexpected result: {res:?}"),
+    }
+}
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 const PRICES_PATH: &str = "prices";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ListPrices {}
 
 impl Cmd for ListPrices {
@@ -42,3 +43,41 @@ fn test_json() {
     "#;
     crate::cmd::check_cmd_json::<ListPrices>(None, Some(output_json));
 }
+
+#[test]
+fn test_unknown_fields_json() {
+    let output_json = r#"
+    {
+        "subscription": [{
+            "months": 12,
+            "usd_cents": 8000,
+            "regular_usd_cents": 9600,
+            "future_price_field": "EUR",
+            "sale": {
+                "title": "Yearly Discount",
+                "summary": "Yearly Subscritpion Discount",
+                "future_sale_field": 1
+            }
+        }],
+        "top_up": [],
+        "sale": null,
+        "future_prices_field": []
+    }
+    "#;
+    let known_output_json = r#"
+    {
+        "subscription": [{
+            "months": 12,
+            "usd_cents": 8000,
+            "regular_usd_cents": 9600,
+            "sale": {
+                "title": "Yearly Discount",
+                "summary": "Yearly Subscritpion Discount"
+            }
+        }],
+        "top_up": [],
+        "sale": null
+    }
+    "#;
+    crate::cmd::check_cmd_unknown_fields_json::<ListPrices>(output_json, known_output_json);
+}
@@ -7,6 +7,7 @@ use super::Cmd;
 const RELAYS_PATH: &str = "relays";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ListRelays {}
 
 impl Cmd for ListRelays {
@@ -27,3 +28,25 @@ fn test_json() {
     ]"#;
     crate::cmd::check_cmd_json::<ListRelays>(None, Some(output_json));
 }
+
+#[test]
+fn test_unknown_fields_json() {
+    let output_json = r#"[
+  {
+    "id": "NYC-001",
+    "ip_v4": "8.8.31.3",
+    "ip_v6": "2001:db8:1234:ffff:ffff:ffff:ffff:ffff",
+    "preferred_exits": [{ "id": "nyc-wg-30", "future_preference_field": 1 }],
+    "future_relay_field": true
+  }
+    ]"#;
+    let known_output_json = r#"[
+  {
+    "id": "NYC-001",
+    "ip_v4": "8.8.31.3",
+    "ip_v6": "2001:db8:1234:ffff:ffff:ffff:ffff:ffff",
+    "preferred_exits": [{ "id": "nyc-wg-30" }]
+  }
+    ]"#;
+    crate::cmd::check_cmd_unknown_fields_json::<ListRelays>(output_json, known_output_json);
+}
@@ -5,6 +5,7 @@ const CREATE_STRIPE_SUBSCRIPTION_CHECKOUT_PATH: &str = "stripe/create_checkout_s
 const CREATE_PORTAL_SESSION_CHECKOUT_PATH: &str = "stripe/create_portal_session";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CreateStripeSubscriptionCheckout {}
 
 impl Default for CreateStripeSubscriptionCheckout {
@@ -20,6 +21,7 @@ impl CreateStripeSubscriptionCheckout {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CreateStripeSubscriptionCheckoutOutput {
     pub checkout_url: String,
 }
@@ -37,6 +39,7 @@ impl Cmd for CreateStripeSubscriptionCheckout {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CreateStripeManageSubscriptionSession {
     pub session_id: String,
 }
@@ -48,6 +51,7 @@ impl CreateStripeManageSubscriptionSession {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CreateStripeManageSubscriptionSessionOutput {
     pub portal_url: String,
 }
@@ -4,11 +4,13 @@ use serde::{Deserialize, Serialize};
 const STRIPE_TOP_UP_PATH: &str = "stripe/top_up";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct StripeTopUpInfo {
     pub payment_intent_client_secret: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CreateStripeTopUp {
     pub months: u16,
 }
@@ -2,6 +2,7 @@ use crate::cmd::Cmd;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct DeleteTunnel {
     pub id: String,
 }
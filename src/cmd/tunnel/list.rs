@@ -3,6 +3,7 @@ use crate::types::OneTunnel;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ListTunnels {}
 impl Cmd for ListTunnels {
     type Output = Vec<OneTunnel>;
@@ -55,3 +56,40 @@ fn test_json() {
     "#;
     crate::cmd::check_cmd_json::<ListTunnels>(None, Some(output_json));
 }
+
+#[test]
+fn test_unknown_tunnel_json() {
+    let output_json = r#"
+    [{
+      "id": "dc799918-7738-446f-b1fc-ae3ba98103c7",
+      "status": {
+        "type": "hibernating",
+        "since": 1725050273
+      },
+      "config": {
+        "type": "quic",
+        "client_pubkey": "wjaiHUEOJ8k3X+U3b6H6yTcipqFipIbFQSB0CwZDNlQ=",
+        "future_field": [1, 2, 3]
+      },
+      "relay": {
+        "id": "NYC-001",
+        "ip_v4": "8.8.31.3",
+        "ip_v6": "2001:db8:1234:ffff:ffff:ffff:ffff:ffff",
+        "preferred_exits": [{ "id": "nyc-wg-30" }]
+      },
+      "exit": {
+        "id": "NYC-001",
+        "country_code": "US",
+        "city_code": "nyc",
+        "city_name": "New York"
+      }
+    }]
+    "#;
+    crate::cmd::check_cmd_json::<ListTunnels>(None, Some(output_json));
+
+    let tunnels: Vec<OneTunnel> = serde_json::from_str(output_json).unwrap();
+    assert!(tunnels[0].status.is_unknown());
+    assert_eq!(tunnels[0].status.when(), None);
+    assert!(tunnels[0].config.is_unknown());
+    assert!(!tunnels[0].is_supported());
+}
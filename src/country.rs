@@ -0,0 +1,476 @@
+//! ISO 3166-1 alpha-2 country codes with English names, flags and regions.
+//!
+//! A shared table, so that every app shows the same names for the countries in [`OneExit::country_code`](crate::types::OneExit::country_code).
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::types::Coordinates;
+
+/// Continental regions, following the UN M49 standard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Region {
+    Africa,
+    Americas,
+    Antarctica,
+    Asia,
+    Europe,
+    Oceania,
+}
+
+impl Region {
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::Africa => "Africa",
+            Region::Americas => "Americas",
+            Region::Antarctica => "Antarctica",
+            Region::Asia => "Asia",
+            Region::Europe => "Europe",
+            Region::Oceania => "Oceania",
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+type CountryEntry = (&'static str, &'static str, Region, Option<(f64, f64)>);
+
+/// Countries by code, sorted by code: English short name, region and a representative location as (latitude, longitude).
+///
+/// Locations are the capital city, so that distances are measured from where most users are likely to be. Territories without a capital have none.
+const COUNTRIES: &[CountryEntry] = &[
+    ("AD", "Andorra", Region::Europe, Some((42.51, 1.52))),
+    ("AE", "United Arab Emirates", Region::Asia, Some((24.45, 54.38))),
+    ("AF", "Afghanistan", Region::Asia, Some((34.53, 69.17))),
+    ("AG", "Antigua and Barbuda", Region::Americas, Some((17.12, -61.85))),
+    ("AI", "Anguilla", Region::Americas, Some((18.22, -63.05))),
+    ("AL", "Albania", Region::Europe, Some((41.33, 19.82))),
+    ("AM", "Armenia", Region::Asia, Some((40.18, 44.51))),
+    ("AO", "Angola", Region::Africa, Some((-8.84, 13.23))),
+    ("AQ", "Antarctica", Region::Antarctica, None),
+    ("AR", "Argentina", Region::Americas, Some((-34.6, -58.38))),
+    ("AS", "American Samoa", Region::Oceania, Some((-14.28, -170.7))),
+    ("AT", "Austria", Region::Europe, Some((48.21, 16.37))),
+    ("AU", "Australia", Region::Oceania, Some((-35.28, 149.13))),
+    ("AW", "Aruba", Region::Americas, Some((12.52, -70.03))),
+    ("AX", "Åland Islands", Region::Europe, Some((60.1, 19.94))),
+    ("AZ", "Azerbaijan", Region::Asia, Some((40.41, 49.87))),
+    ("BA", "Bosnia and Herzegovina", Region::Europe, Some((43.86, 18.41))),
+    ("BB", "Barbados", Region::Americas, Some((13.1, -59.62))),
+    ("BD", "Bangladesh", Region::Asia, Some((23.81, 90.41))),
+    ("BE", "Belgium", Region::Europe, Some((50.85, 4.35))),
+    ("BF", "Burkina Faso", Region::Africa, Some((12.37, -1.52))),
+    ("BG", "Bulgaria", Region::Europe, Some((42.7, 23.32))),
+    ("BH", "Bahrain", Region::Asia, Some((26.23, 50.59))),
+    ("BI", "Burundi", Region::Africa, Some((-3.43, 29.93))),
+    ("BJ", "Benin", Region::Africa, Some((6.5, 2.6))),
+    ("BL", "Saint Barthélemy", Region::Americas, Some((17.9, -62.85))),
+    ("BM", "Bermuda", Region::Americas, Some((32.29, -64.78))),
+    ("BN", "Brunei", Region::Asia, Some((4.89, 114.94))),
+    ("BO", "Bolivia", Region::Americas, Some((-16.5, -68.15))),
+    ("BQ", "Caribbean Netherlands", Region::Americas, Some((12.15, -68.27))),
+    ("BR", "Brazil", Region::Americas, Some((-15.79, -47.88))),
+    ("BS", "Bahamas", Region::Americas, Some((25.05, -77.35))),
+    ("BT", "Bhutan", Region::Asia, Some((27.47, 89.64))),
+    ("BV", "Bouvet Island", Region::Americas, None),
+    ("BW", "Botswana", Region::Africa, Some((-24.65, 25.91))),
+    ("BY", "Belarus", Region::Europe, Some((53.9, 27.57))),
+    ("BZ", "Belize", Region::Americas, Some((17.25, -88.77))),
+    ("CA", "Canada", Region::Americas, Some((45.42, -75.7))),
+    ("CC", "Cocos (Keeling) Islands", Region::Oceania, Some((-12.19, 96.83))),
+    ("CD", "Democratic Republic of the Congo", Region::Africa, Some((-4.32, 15.31))),
+    ("CF", "Central African Republic", Region::Africa, Some((4.39, 18.56))),
+    ("CG", "Congo", Region::Africa, Some((-4.27, 15.28))),
+    ("CH", "Switzerland", Region::Europe, Some((46.95, 7.45))),
+    ("CI", "Côte d'Ivoire", Region::Africa, Some((6.83, -5.29))),
+    ("CK", "Cook Islands", Region::Oceania, Some((-21.21, -159.78))),
+    ("CL", "Chile", Region::Americas, Some((-33.45, -70.67))),
+    ("CM", "Cameroon", Region::Africa, Some((3.87, 11.52))),
+    ("CN", "China", Region::Asia, Some((39.9, 116.41))),
+    ("CO", "Colombia", Region::Americas, Some((4.71, -74.07))),
+    ("CR", "Costa Rica", Region::Americas, Some((9.93, -84.08))),
+    ("CU", "Cuba", Region::Americas, Some((23.11, -82.37))),
+    ("CV", "Cabo Verde", Region::Africa, Some((14.93, -23.51))),
+    ("CW", "Curaçao", Region::Americas, Some((12.11, -68.93))),
+    ("CX", "Christmas Island", Region::Oceania, Some((-10.42, 105.68))),
+    ("CY", "Cyprus", Region::Asia, Some((35.19, 33.38))),
+    ("CZ", "Czechia", Region::Europe, Some((50.08, 14.44))),
+    ("DE", "Germany", Region::Europe, Some((52.52, 13.4))),
+    ("DJ", "Djibouti", Region::Africa, Some((11.59, 43.15))),
+    ("DK", "Denmark", Region::Europe, Some((55.68, 12.57))),
+    ("DM", "Dominica", Region::Americas, Some((15.3, -61.39))),
+    ("DO", "Dominican Republic", Region::Americas, Some((18.49, -69.93))),
+    ("DZ", "Algeria", Region::Africa, Some((36.75, 3.06))),
+    ("EC", "Ecuador", Region::Americas, Some((-0.18, -78.47))),
+    ("EE", "Estonia", Region::Europe, Some((59.44, 24.75))),
+    ("EG", "Egypt", Region::Africa, Some((30.04, 31.24))),
+    ("EH", "Western Sahara", Region::Africa, Some((27.15, -13.2))),
+    ("ER", "Eritrea", Region::Africa, Some((15.32, 38.93))),
+    ("ES", "Spain", Region::Europe, Some((40.42, -3.7))),
+    ("ET", "Ethiopia", Region::Africa, Some((9.03, 38.74))),
+    ("FI", "Finland", Region::Europe, Some((60.17, 24.94))),
+    ("FJ", "Fiji", Region::Oceania, Some((-18.14, 178.44))),
+    ("FK", "Falkland Islands", Region::Americas, Some((-51.7, -57.85))),
+    ("FM", "Micronesia", Region::Oceania, Some((6.92, 158.16))),
+    ("FO", "Faroe Islands", Region::Europe, Some((62.01, -6.77))),
+    ("FR", "France", Region::Europe, Some((48.86, 2.35))),
+    ("GA", "Gabon", Region::Africa, Some((0.39, 9.45))),
+    ("GB", "United Kingdom", Region::Europe, Some((51.51, -0.13))),
+    ("GD", "Grenada", Region::Americas, Some((12.05, -61.75))),
+    ("GE", "Georgia", Region::Asia, Some((41.72, 44.79))),
+    ("GF", "French Guiana", Region::Americas, Some((4.92, -52.33))),
+    ("GG", "Guernsey", Region::Europe, Some((49.46, -2.54))),
+    ("GH", "Ghana", Region::Africa, Some((5.6, -0.19))),
+    ("GI", "Gibraltar", Region::Europe, Some((36.14, -5.35))),
+    ("GL", "Greenland", Region::Americas, Some((64.18, -51.72))),
+    ("GM", "Gambia", Region::Africa, Some((13.45, -16.58))),
+    ("GN", "Guinea", Region::Africa, Some((9.51, -13.71))),
+    ("GP", "Guadeloupe", Region::Americas, Some((16.0, -61.73))),
+    ("GQ", "Equatorial Guinea", Region::Africa, Some((3.75, 8.78))),
+    ("GR", "Greece", Region::Europe, Some((37.98, 23.73))),
+    (
+        "GS",
+        "South Georgia and the South Sandwich Islands",
+        Region::Americas,
+        Some((-54.28, -36.51)),
+    ),
+    ("GT", "Guatemala", Region::Americas, Some((14.63, -90.51))),
+    ("GU", "Guam", Region::Oceania, Some((13.47, 144.75))),
+    ("GW", "Guinea-Bissau", Region::Africa, Some((11.86, -15.6))),
+    ("GY", "Guyana", Region::Americas, Some((6.8, -58.16))),
+    ("HK", "Hong Kong", Region::Asia, Some((22.28, 114.16))),
+    ("HM", "Heard Island and McDonald Islands", Region::Oceania, None),
+    ("HN", "Honduras", Region::Americas, Some((14.07, -87.19))),
+    ("HR", "Croatia", Region::Europe, Some((45.81, 15.98))),
+    ("HT", "Haiti", Region::Americas, Some((18.54, -72.34))),
+    ("HU", "Hungary", Region::Europe, Some((47.5, 19.04))),
+    ("ID", "Indonesia", Region::Asia, Some((-6.21, 106.85))),
+    ("IE", "Ireland", Region::Europe, Some((53.35, -6.26))),
+    ("IL", "Israel", Region::Asia, Some((31.78, 35.22))),
+    ("IM", "Isle of Man", Region::Europe, Some((54.15, -4.48))),
+    ("IN", "India", Region::Asia, Some((28.61, 77.21))),
+    ("IO", "British Indian Ocean Territory", Region::Africa, Some((-7.31, 72.41))),
+    ("IQ", "Iraq", Region::Asia, Some((33.31, 44.36))),
+    ("IR", "Iran", Region::Asia, Some((35.69, 51.39))),
+    ("IS", "Iceland", Region::Europe, Some((64.15, -21.94))),
+    ("IT", "Italy", Region::Europe, Some((41.9, 12.5))),
+    ("JE", "Jersey", Region::Europe, Some((49.19, -2.11))),
+    ("JM", "Jamaica", Region::Americas, Some((17.97, -76.79))),
+    ("JO", "Jordan", Region::Asia, Some((31.95, 35.93))),
+    ("JP", "Japan", Region::Asia, Some((35.68, 139.69))),
+    ("KE", "Kenya", Region::Africa, Some((-1.29, 36.82))),
+    ("KG", "Kyrgyzstan", Region::Asia, Some((42.87, 74.59))),
+    ("KH", "Cambodia", Region::Asia, Some((11.56, 104.93))),
+    ("KI", "Kiribati", Region::Oceania, Some((1.33, 172.98))),
+    ("KM", "Comoros", Region::Africa, Some((-11.7, 43.26))),
+    ("KN", "Saint Kitts and Nevis", Region::Americas, Some((17.3, -62.73))),
+    ("KP", "North Korea", Region::Asia, Some((39.02, 125.75))),
+    ("KR", "South Korea", Region::Asia, Some((37.57, 126.98))),
+    ("KW", "Kuwait", Region::Asia, Some((29.38, 47.99))),
+    ("KY", "Cayman Islands", Region::Americas, Some((19.29, -81.38))),
+    ("KZ", "Kazakhstan", Region::Asia, Some((51.17, 71.45))),
+    ("LA", "Laos", Region::Asia, Some((17.97, 102.63))),
+    ("LB", "Lebanon", Region::Asia, Some((33.89, 35.5))),
+    ("LC", "Saint Lucia", Region::Americas, Some((14.01, -60.99))),
+    ("LI", "Liechtenstein", Region::Europe, Some((47.14, 9.52))),
+    ("LK", "Sri Lanka", Region::Asia, Some((6.89, 79.92))),
+    ("LR", "Liberia", Region::Africa, Some((6.31, -10.8))),
+    ("LS", "Lesotho", Region::Africa, Some((-29.31, 27.48))),
+    ("LT", "Lithuania", Region::Europe, Some((54.69, 25.28))),
+    ("LU", "Luxembourg", Region::Europe, Some((49.61, 6.13))),
+    ("LV", "Latvia", Region::Europe, Some((56.95, 24.11))),
+    ("LY", "Libya", Region::Africa, Some((32.89, 13.19))),
+    ("MA", "Morocco", Region::Africa, Some((34.02, -6.84))),
+    ("MC", "Monaco", Region::Europe, Some((43.74, 7.42))),
+    ("MD", "Moldova", Region::Europe, Some((47.01, 28.86))),
+    ("ME", "Montenegro", Region::Europe, Some((42.44, 19.26))),
+    ("MF", "Saint Martin", Region::Americas, Some((18.07, -63.08))),
+    ("MG", "Madagascar", Region::Africa, Some((-18.88, 47.51))),
+    ("MH", "Marshall Islands", Region::Oceania, Some((7.09, 171.38))),
+    ("MK", "North Macedonia", Region::Europe, Some((42.0, 21.43))),
+    ("ML", "Mali", Region::Africa, Some((12.64, -8.0))),
+    ("MM", "Myanmar", Region::Asia, Some((19.76, 96.08))),
+    ("MN", "Mongolia", Region::Asia, Some((47.89, 106.91))),
+    ("MO", "Macao", Region::Asia, Some((22.2, 113.54))),
+    ("MP", "Northern Mariana Islands", Region::Oceania, Some((15.18, 145.75))),
+    ("MQ", "Martinique", Region::Americas, Some((14.6, -61.07))),
+    ("MR", "Mauritania", Region::Africa, Some((18.08, -15.98))),
+    ("MS", "Montserrat", Region::Americas, Some((16.79, -62.21))),
+    ("MT", "Malta", Region::Europe, Some((35.9, 14.51))),
+    ("MU", "Mauritius", Region::Africa, Some((-20.16, 57.5))),
+    ("MV", "Maldives", Region::Asia, Some((4.18, 73.51))),
+    ("MW", "Malawi", Region::Africa, Some((-13.96, 33.79))),
+    ("MX", "Mexico", Region::Americas, Some((19.43, -99.13))),
+    ("MY", "Malaysia", Region::Asia, Some((3.15, 101.69))),
+    ("MZ", "Mozambique", Region::Africa, Some((-25.97, 32.57))),
+    ("NA", "Namibia", Region::Africa, Some((-22.56, 17.08))),
+    ("NC", "New Caledonia", Region::Oceania, Some((-22.27, 166.46))),
+    ("NE", "Niger", Region::Africa, Some((13.51, 2.13))),
+    ("NF", "Norfolk Island", Region::Oceania, Some((-29.06, 167.96))),
+    ("NG", "Nigeria", Region::Africa, Some((9.08, 7.4))),
+    ("NI", "Nicaragua", Region::Americas, Some((12.11, -86.24))),
+    ("NL", "Netherlands", Region::Europe, Some((52.37, 4.9))),
+    ("NO", "Norway", Region::Europe, Some((59.91, 10.75))),
+    ("NP", "Nepal", Region::Asia, Some((27.72, 85.32))),
+    ("NR", "Nauru", Region::Oceania, Some((-0.55, 166.92))),
+    ("NU", "Niue", Region::Oceania, Some((-19.06, -169.92))),
+    ("NZ", "New Zealand", Region::Oceania, Some((-41.29, 174.78))),
+    ("OM", "Oman", Region::Asia, Some((23.59, 58.41))),
+    ("PA", "Panama", Region::Americas, Some((8.98, -79.52))),
+    ("PE", "Peru", Region::Americas, Some((-12.05, -77.04))),
+    ("PF", "French Polynesia", Region::Oceania, Some((-17.53, -149.57))),
+    ("PG", "Papua New Guinea", Region::Oceania, Some((-9.44, 147.18))),
+    ("PH", "Philippines", Region::Asia, Some((14.6, 120.98))),
+    ("PK", "Pakistan", Region::Asia, Some((33.68, 73.05))),
+    ("PL", "Poland", Region::Europe, Some((52.23, 21.01))),
+    ("PM", "Saint Pierre and Miquelon", Region::Americas, Some((46.78, -56.18))),
+    ("PN", "Pitcairn", Region::Oceania, Some((-25.07, -130.1))),
+    ("PR", "Puerto Rico", Region::Americas, Some((18.47, -66.11))),
+    ("PS", "Palestine", Region::Asia, Some((31.9, 35.2))),
+    ("PT", "Portugal", Region::Europe, Some((38.72, -9.14))),
+    ("PW", "Palau", Region::Oceania, Some((7.5, 134.62))),
+    ("PY", "Paraguay", Region::Americas, Some((-25.26, -57.58))),
+    ("QA", "Qatar", Region::Asia, Some((25.29, 51.53))),
+    ("RE", "Réunion", Region::Africa, Some((-20.88, 55.45))),
+    ("RO", "Romania", Region::Europe, Some((44.43, 26.1))),
+    ("RS", "Serbia", Region::Europe, Some((44.79, 20.45))),
+    ("RU", "Russia", Region::Europe, Some((55.76, 37.62))),
+    ("RW", "Rwanda", Region::Africa, Some((-1.95, 30.06))),
+    ("SA", "Saudi Arabia", Region::Asia, Some((24.71, 46.68))),
+    ("SB", "Solomon Islands", Region::Oceania, Some((-9.43, 159.95))),
+    ("SC", "Seychelles", Region::Africa, Some((-4.62, 55.45))),
+    ("SD", "Sudan", Region::Africa, Some((15.5, 32.56))),
+    ("SE", "Sweden", Region::Europe, Some((59.33, 18.07))),
+    ("SG", "Singapore", Region::Asia, Some((1.29, 103.85))),
+    ("SH", "Saint Helena", Region::Africa, Some((-15.92, -5.72))),
+    ("SI", "Slovenia", Region::Europe, Some((46.06, 14.51))),
+    ("SJ", "Svalbard and Jan Mayen", Region::Europe, Some((78.22, 15.65))),
+    ("SK", "Slovakia", Region::Europe, Some((48.15, 17.11))),
+    ("SL", "Sierra Leone", Region::Africa, Some((8.48, -13.23))),
+    ("SM", "San Marino", Region::Europe, Some((43.94, 12.45))),
+    ("SN", "Senegal", Region::Africa, Some((14.72, -17.47))),
+    ("SO", "Somalia", Region::Africa, Some((2.05, 45.32))),
+    ("SR", "Suriname", Region::Americas, Some((5.85, -55.2))),
+    ("SS", "South Sudan", Region::Africa, Some((4.85, 31.58))),
+    ("ST", "Sao Tome and Principe", Region::Africa, Some((0.34, 6.73))),
+    ("SV", "El Salvador", Region::Americas, Some((13.69, -89.22))),
+    ("SX", "Sint Maarten", Region::Americas, Some((18.03, -63.05))),
+    ("SY", "Syria", Region::Asia, Some((33.51, 36.28))),
+    ("SZ", "Eswatini", Region::Africa, Some((-26.31, 31.14))),
+    ("TC", "Turks and Caicos Islands", Region::Americas, Some((21.46, -71.14))),
+    ("TD", "Chad", Region::Africa, Some((12.13, 15.06))),
+    ("TF", "French Southern Territories", Region::Africa, Some((-49.35, 70.22))),
+    ("TG", "Togo", Region::Africa, Some((6.13, 1.22))),
+    ("TH", "Thailand", Region::Asia, Some((13.76, 100.5))),
+    ("TJ", "Tajikistan", Region::Asia, Some((38.56, 68.79))),
+    ("TK", "Tokelau", Region::Oceania, None),
+    ("TL", "Timor-Leste", Region::Asia, Some((-8.56, 125.57))),
+    ("TM", "Turkmenistan", Region::Asia, Some((37.96, 58.33))),
+    ("TN", "Tunisia", Region::Africa, Some((36.81, 10.18))),
+    ("TO", "Tonga", Region::Oceania, Some((-21.14, -175.2))),
+    ("TR", "Türkiye", Region::Asia, Some((39.93, 32.86))),
+    ("TT", "Trinidad and Tobago", Region::Americas, Some((10.66, -61.51))),
+    ("TV", "Tuvalu", Region::Oceania, Some((-8.52, 179.2))),
+    ("TW", "Taiwan", Region::Asia, Some((25.03, 121.57))),
+    ("TZ", "Tanzania", Region::Africa, Some((-6.16, 35.75))),
+    ("UA", "Ukraine", Region::Europe, Some((50.45, 30.52))),
+    ("UG", "Uganda", Region::Africa, Some((0.35, 32.58))),
+    ("UM", "United States Minor Outlying Islands", Region::Oceania, None),
+    ("US", "United States", Region::Americas, Some((38.9, -77.04))),
+    ("UY", "Uruguay", Region::Americas, Some((-34.9, -56.19))),
+    ("UZ", "Uzbekistan", Region::Asia, Some((41.3, 69.24))),
+    ("VA", "Vatican City", Region::Europe, Some((41.9, 12.45))),
+    ("VC", "Saint Vincent and the Grenadines", Region::Americas, Some((13.16, -61.22))),
+    ("VE", "Venezuela", Region::Americas, Some((10.49, -66.88))),
+    ("VG", "British Virgin Islands", Region::Americas, Some((18.43, -64.62))),
+    ("VI", "U.S. Virgin Islands", Region::Americas, Some((18.34, -64.93))),
+    ("VN", "Vietnam", Region::Asia, Some((21.03, 105.85))),
+    ("VU", "Vanuatu", Region::Oceania, Some((-17.73, 168.32))),
+    ("WF", "Wallis and Futuna", Region::Oceania, Some((-13.28, -176.17))),
+    ("WS", "Samoa", Region::Oceania, Some((-13.83, -171.77))),
+    ("YE", "Yemen", Region::Asia, Some((15.37, 44.19))),
+    ("YT", "Mayotte", Region::Africa, Some((-12.78, 45.23))),
+    ("ZA", "South Africa", Region::Africa, Some((-25.75, 28.19))),
+    ("ZM", "Zambia", Region::Africa, Some((-15.39, 28.32))),
+    ("ZW", "Zimbabwe", Region::Africa, Some((-17.83, 31.05))),
+];
+
+/// An ISO 3166-1 alpha-2 country code.
+///
+/// Codes which aren't in the bundled table are kept, so that countries added on the server don't break older clients. They have no name, flag or region.
+///
+/// Codes from user input are converted to upper case, while deserialization keeps the server's string as is. Comparisons, hashing and table lookups ignore ASCII case.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(type = "string"))]
+pub struct CountryCode(String);
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("expected two ASCII letters, found {:?}", .0)]
+pub struct ParseCountryCodeError(pub String);
+
+impl CountryCode {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn entry(&self) -> Option<&'static CountryEntry> {
+        let upper = self.0.to_ascii_uppercase();
+        let i = COUNTRIES.binary_search_by(|(code, ..)| (*code).cmp(upper.as_str())).ok()?;
+        Some(&COUNTRIES[i])
+    }
+
+    /// Whether the code is in the bundled table.
+    pub fn is_known(&self) -> bool {
+        self.entry().is_some()
+    }
+
+    /// English short name, for example "United States".
+    pub fn name(&self) -> Option<&'static str> {
+        self.entry().map(|(_, name, _, _)| *name)
+    }
+
+    pub fn region(&self) -> Option<Region> {
+        self.entry().map(|(_, _, region, _)| *region)
+    }
+
+    /// The flag emoji, made of the regional indicator symbols for the code.
+    pub fn flag(&self) -> Option<String> {
+        Some(
+            self.entry()?
+                .0
+                .chars()
+                .map(|c| char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)).unwrap())
+                .collect(),
+        )
+    }
+
+    /// A representative location of the country.
+    pub fn location(&self) -> Option<Coordinates> {
+        self.entry()?.3.map(|(latitude, longitude)| Coordinates::new(latitude, longitude))
+    }
+
+    /// The name if known, otherwise the code.
+    pub fn display_name(&self) -> &str {
+        self.name().unwrap_or(&self.0)
+    }
+}
+
+/// Keeps any string, only converting it to upper case. Use [`FromStr`] to reject malformed codes.
+impl From<&str> for CountryCode {
+    fn from(code: &str) -> Self {
+        Self(code.to_ascii_uppercase())
+    }
+}
+
+impl From<String> for CountryCode {
+    fn from(mut code: String) -> Self {
+        code.make_ascii_uppercase();
+        Self(code)
+    }
+}
+
+impl FromStr for CountryCode {
+    type Err = ParseCountryCodeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 2 || !s.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(ParseCountryCodeError(s.to_string()));
+        }
+        Ok(Self::from(s))
+    }
+}
+
+impl PartialEq for CountryCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl Eq for CountryCode {}
+
+impl PartialOrd for CountryCode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CountryCode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let upper = |code: &Self| code.0.bytes().map(|b| b.to_ascii_uppercase()).collect::<Vec<_>>();
+        upper(self).cmp(&upper(other))
+    }
+}
+
+impl Hash for CountryCode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_ascii_uppercase().hash(state);
+    }
+}
+
+impl Display for CountryCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for CountryCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for CountryCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+/// All countries in the bundled table, sorted by code.
+pub fn all() -> impl Iterator<Item = CountryCode> {
+    COUNTRIES.iter().map(|(code, ..)| CountryCode::from(*code))
+}
+
+#[test]
+fn test_country_code() {
+    assert!(COUNTRIES.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(COUNTRIES.iter().all(|(code, ..)| code.parse::<CountryCode>().is_ok()));
+
+    let us: CountryCode = "us".parse().unwrap();
+    assert_eq!(us.as_str(), "US");
+    assert_eq!(us.name(), Some("United States"));
+    assert_eq!(us.region(), Some(Region::Americas));
+    assert_eq!(us.flag().as_deref(), Some("\u{1F1FA}\u{1F1F8}"));
+    assert_eq!(CountryCode::from("CH").flag().as_deref(), Some("🇨🇭"));
+    assert_eq!(CountryCode::from("TR").region(), Some(Region::Asia));
+    let ch = CountryCode::from("ch").location().unwrap();
+    assert!((ch.latitude - 46.95).abs() < 0.01 && (ch.longitude - 7.45).abs() < 0.01);
+
+    let future: CountryCode = serde_json::from_str(r#""xx""#).unwrap();
+    assert_eq!(future.as_str(), "xx");
+    assert_eq!(future, CountryCode::from("XX"));
+    assert!(!future.is_known());
+    assert_eq!(
+        (future.name(), future.flag(), future.region(), future.location()),
+        (None, None, None, None)
+    );
+    assert_eq!(future.display_name(), "xx");
+    assert_eq!(serde_json::to_string(&future).unwrap(), r#""xx""#);
+
+    let lower: CountryCode = serde_json::from_str(r#""ch""#).unwrap();
+    assert_eq!(lower.as_str(), "ch");
+    assert_eq!(lower.name(), Some("Switzerland"));
+    assert_eq!(lower.flag().as_deref(), Some("🇨🇭"));
+    assert_eq!(lower.cmp(&CountryCode::from("CH")), Ordering::Equal);
+
+    assert_eq!("USA".parse::<CountryCode>(), Err(ParseCountryCodeError("USA".into())));
+    assert!("U1".parse::<CountryCode>().is_err());
+    assert_eq!(all().count(), COUNTRIES.len());
+}
@@ -0,0 +1,216 @@
+//! Choosing exits by geographic distance.
+//!
+//! Used to suggest a nearby location before any latency measurements are available. Exits without [`OneExit::coordinates`] are placed using a bundled table of city locations.
+
+use std::cmp::Ordering;
+
+use crate::catalog::ExitCatalog;
+use crate::country::CountryCode;
+use crate::types::{Coordinates, OneExit};
+
+/// Approximate city locations as (latitude, longitude), sorted and keyed by the lowercase IATA metropolitan area or airport code used as `city_code`.
+const CITY_LOCATIONS: &[(&str, f64, f64)] = &[
+    ("ams", 52.37, 4.90),
+    ("ath", 37.98, 23.73),
+    ("atl", 33.75, -84.39),
+    ("auh", 24.45, 54.38),
+    ("bcn", 41.39, 2.17),
+    ("beg", 44.79, 20.45),
+    ("ber", 52.52, 13.40),
+    ("bkk", 13.76, 100.50),
+    ("bog", 4.71, -74.07),
+    ("bom", 19.08, 72.88),
+    ("bos", 42.36, -71.06),
+    ("bru", 50.85, 4.35),
+    ("bts", 48.15, 17.11),
+    ("bud", 47.50, 19.04),
+    ("bue", -34.60, -58.38),
+    ("buh", 44.43, 26.10),
+    ("cai", 30.04, 31.24),
+    ("ccs", 10.48, -66.90),
+    ("ccu", 22.57, 88.36),
+    ("cgk", -6.21, 106.85),
+    ("chi", 41.88, -87.63),
+    ("cmb", 6.93, 79.86),
+    ("cph", 55.68, 12.57),
+    ("cpt", -33.92, 18.42),
+    ("dar", -6.79, 39.21),
+    ("del", 28.61, 77.21),
+    ("den", 39.74, -104.99),
+    ("dfw", 32.78, -96.80),
+    ("dub", 53.35, -6.26),
+    ("dus", 51.23, 6.77),
+    ("dxb", 25.20, 55.27),
+    ("edi", 55.95, -3.19),
+    ("fra", 50.11, 8.68),
+    ("gva", 46.20, 6.14),
+    ("ham", 53.55, 9.99),
+    ("hel", 60.17, 24.94),
+    ("hkg", 22.32, 114.17),
+    ("hou", 29.76, -95.37),
+    ("iev", 50.45, 30.52),
+    ("ist", 41.01, 28.98),
+    ("jnb", -26.20, 28.05),
+    ("kul", 3.139, 101.687),
+    ("lax", 34.05, -118.24),
+    ("lim", -12.05, -77.04),
+    ("lis", 38.72, -9.14),
+    ("lon", 51.51, -0.13),
+    ("los", 6.52, 3.38),
+    ("lux", 49.61, 6.13),
+    ("lyo", 45.76, 4.84),
+    ("mad", 40.42, -3.70),
+    ("man", 53.48, -2.24),
+    ("mel", -37.81, 144.96),
+    ("mex", 19.43, -99.13),
+    ("mia", 25.76, -80.19),
+    ("mil", 45.46, 9.19),
+    ("mnl", 14.60, 120.98),
+    ("mow", 55.76, 37.62),
+    ("msp", 44.98, -93.27),
+    ("muc", 48.14, 11.58),
+    ("nbo", -1.29, 36.82),
+    ("nyc", 40.71, -74.01),
+    ("osa", 34.69, 135.50),
+    ("osl", 59.91, 10.75),
+    ("par", 48.86, 2.35),
+    ("per", -31.95, 115.86),
+    ("phx", 33.45, -112.07),
+    ("prg", 50.08, 14.44),
+    ("rek", 64.15, -21.94),
+    ("rio", -22.91, -43.17),
+    ("rix", 56.95, 24.11),
+    ("rom", 41.90, 12.50),
+    ("ruh", 24.71, 46.68),
+    ("sao", -23.55, -46.63),
+    ("scl", -33.45, -70.67),
+    ("sea", 47.61, -122.33),
+    ("sel", 37.57, 126.98),
+    ("sfo", 37.77, -122.42),
+    ("sin", 1.35, 103.82),
+    ("sjc", 37.34, -121.89),
+    ("sof", 42.70, 23.32),
+    ("sto", 59.33, 18.07),
+    ("syd", -33.87, 151.21),
+    ("tll", 59.44, 24.75),
+    ("tlv", 32.09, 34.78),
+    ("tpe", 25.03, 121.57),
+    ("tyo", 35.68, 139.69),
+    ("vie", 48.21, 16.37),
+    ("vno", 54.69, 25.28),
+    ("was", 38.91, -77.04),
+    ("waw", 52.23, 21.01),
+    ("yto", 43.65, -79.38),
+    ("yul", 45.50, -73.57),
+    ("yvr", 49.28, -123.12),
+    ("zag", 45.82, 15.98),
+    ("zrh", 47.38, 8.54),
+];
+
+/// The bundled location of the city with `city_code`, which is matched case-insensitively.
+pub fn city_location(city_code: &str) -> Option<Coordinates> {
+    let city_code = city_code.to_ascii_lowercase();
+    let i = CITY_LOCATIONS.binary_search_by(|(c, _, _)| (*c).cmp(city_code.as_str())).ok()?;
+    let (_, latitude, longitude) = CITY_LOCATIONS[i];
+    Some(Coordinates::new(latitude, longitude))
+}
+
+impl OneExit {
+    /// The exit's coordinates, falling back to the bundled location of its city.
+    pub fn location(&self) -> Option<Coordinates> {
+        self.coordinates.or_else(|| city_location(&self.city_code))
+    }
+}
+
+/// Where to measure distances from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Origin {
+    Location(Coordinates),
+    /// Exits in the country rank before all others.
+    Country(CountryCode),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitDistance<'a> {
+    pub exit: &'a OneExit,
+    /// Great-circle distance from the origin, or `None` if the exit's or origin's location is unknown.
+    pub distance_km: Option<f64>,
+}
+
+/// Exits ordered from nearest to farthest.
+///
+/// Exits with an unknown distance come last, in their original order.
+pub fn nearest_exits<'a>(exits: impl IntoIterator<Item = &'a OneExit>, origin: &Origin) -> Vec<ExitDistance<'a>> {
+    let (from, country_code) = match origin {
+        Origin::Location(location) => (Some(*location), None),
+        Origin::Country(country_code) => (country_code.location(), Some(country_code)),
+    };
+    let in_country = |exit: &OneExit| country_code.is_some_and(|code| *code == exit.country_code);
+
+    let mut ranked: Vec<ExitDistance> = exits
+        .into_iter()
+        .map(|exit| ExitDistance {
+            exit,
+            distance_km: from.zip(exit.location()).map(|(from, location)| from.distance_km(&location)),
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        let by_distance = match (a.distance_km, b.distance_km) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        in_country(b.exit).cmp(&in_country(a.exit)).then(by_distance)
+    });
+    ranked
+}
+
+impl ExitCatalog {
+    /// All exits ordered from nearest to farthest, see [`nearest_exits`].
+    pub fn nearest(&self, origin: &Origin) -> Vec<ExitDistance<'_>> {
+        nearest_exits(self.exits(), origin)
+    }
+}
+
+#[test]
+fn test_distance() {
+    let nyc = city_location("NYC").unwrap();
+    let lon = city_location("lon").unwrap();
+    assert!((nyc.distance_km(&lon) - 5570.0).abs() < 10.0);
+    assert_eq!(nyc.distance_km(&nyc), 0.0);
+    assert!(CITY_LOCATIONS.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[test]
+fn test_nearest_exits() {
+    let exit = |id: &str, country_code: &str, city_code: &str, coordinates: Option<Coordinates>| {
+        let mut exit = crate::test_support::exit(id, country_code, city_code, "");
+        exit.coordinates = coordinates;
+        exit
+    };
+    let catalog = ExitCatalog::new(vec![
+        exit("atlantis-1", "XX", "atl-is", None),
+        exit("nyc-1", "US", "nyc", None),
+        exit("zrh-1", "CH", "zrh", None),
+        exit("par-1", "FR", "par", None),
+        exit("lax-1", "US", "lax", Some(Coordinates::new(34.05, -118.24))),
+    ]);
+    let ids = |origin: &Origin| catalog.nearest(origin).iter().map(|d| d.exit.id.as_str()).collect::<Vec<_>>();
+
+    let geneva = Coordinates::new(46.20, 6.14);
+    assert_eq!(ids(&Origin::Location(geneva)), ["zrh-1", "par-1", "nyc-1", "lax-1", "atlantis-1"]);
+    let nearest = &catalog.nearest(&Origin::Location(geneva))[0];
+    assert!(nearest.distance_km.is_some_and(|d| (200.0..250.0).contains(&d)));
+    assert_eq!(catalog.nearest(&Origin::Location(geneva))[4].distance_km, None);
+
+    assert_eq!(ids(&Origin::Country("us".into())), ["nyc-1", "lax-1", "par-1", "zrh-1", "atlantis-1"]);
+    assert_eq!(ids(&Origin::Country("DE".into()))[..2], ["zrh-1", "par-1"]);
+
+    let spain = ExitCatalog::new(vec![
+        exit("lpa-1", "ES", "lpa", Some(Coordinates::new(28.12, -15.44))),
+        exit("mad-1", "ES", "mad", Some(Coordinates::new(40.42, -3.70))),
+    ]);
+    let ids: Vec<_> = spain.nearest(&Origin::Country("ES".into())).iter().map(|d| d.exit.id.as_str()).collect();
+    assert_eq!(ids, ["mad-1", "lpa-1"]);
+}
@@ -0,0 +1,285 @@
+//! Localization of user-facing messages.
+//!
+//! [`AcceptLanguage`] tells the server which languages the user prefers. The server names the language of its messages in the `Content-Language` header, and the bundled catalog provides translated error messages for when the server didn't reply in a language the user accepts.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::cmd::{ApiError, ApiErrorKind};
+
+/// The user's preferred languages as BCP 47 language tags, most preferred first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcceptLanguage {
+    languages: Vec<String>,
+}
+
+impl AcceptLanguage {
+    /// Tags which aren't syntactically valid are dropped.
+    pub fn new(languages: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let languages = languages.into_iter().map(Into::into).filter(|tag| is_valid_language_tag(tag)).collect();
+        Self { languages }
+    }
+
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.languages.iter().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+
+    /// The value for the `Accept-Language` header, or `None` if there are no languages.
+    pub fn to_header_value(&self) -> Option<http::HeaderValue> {
+        if self.is_empty() {
+            return None;
+        }
+        // Only contains validated language tags and q-values.
+        Some(http::HeaderValue::from_str(&self.to_string()).unwrap())
+    }
+}
+
+impl Display for AcceptLanguage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, language) in self.languages.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(language)?;
+            // q-values have at most three decimal places, so later languages share the lowest value.
+            let q = 1000usize.saturating_sub(i * 100).max(1);
+            if q < 1000 {
+                write!(f, ";q={}", format!("0.{q:03}").trim_end_matches('0'))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_language_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .split('-')
+            .all(|subtag| (1..=8).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric()))
+}
+
+fn primary_language(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
+/// Bundled translations of error messages, keyed by language and [`ApiErrorKind::name`].
+const ERROR_MESSAGES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "de",
+        &[
+            ("AccountExpired", "Ihr Konto ist abgelaufen."),
+            ("BadRequest", "Die Anfrage war ungültig."),
+            (
+                "InternalError",
+                "Ein interner Serverfehler ist aufgetreten. Bitte versuchen Sie es später erneut.",
+            ),
+            ("MissingOrInvalidAuthToken", "Die Authentifizierung ist fehlgeschlagen."),
+            ("NoApiRoute", "Diese Funktion ist nicht verfügbar. Bitte aktualisieren Sie die App."),
+            (
+                "NoLongerSupported",
+                "Diese Version der App wird nicht mehr unterstützt. Bitte aktualisieren Sie die App.",
+            ),
+            ("NoMatchingExit", "Für den gewählten Standort ist kein Server verfügbar."),
+            ("RateLimitExceeded", "Zu viele Anfragen. Bitte versuchen Sie es später erneut."),
+            (
+                "SignupLimitExceeded",
+                "In letzter Zeit wurden zu viele Konten erstellt. Bitte versuchen Sie es später erneut.",
+            ),
+            (
+                "TunnelLimitExceeded",
+                "Sie haben die maximale Anzahl an Tunneln erreicht. Bitte löschen Sie einen und versuchen Sie es erneut.",
+            ),
+        ],
+    ),
+    (
+        "en",
+        &[
+            ("AccountExpired", "Your account has expired."),
+            ("BadRequest", "The request was invalid."),
+            ("InternalError", "An internal server error occurred. Please try again later."),
+            ("MissingOrInvalidAuthToken", "Authentication failed."),
+            ("NoApiRoute", "This feature is not available. Please update the app."),
+            (
+                "NoLongerSupported",
+                "This version of the app is no longer supported. Please update the app.",
+            ),
+            ("NoMatchingExit", "No server is available for the selected location."),
+            ("RateLimitExceeded", "Too many requests. Please try again later."),
+            (
+                "SignupLimitExceeded",
+                "Too many accounts have been created recently. Please try again later.",
+            ),
+            (
+                "TunnelLimitExceeded",
+                "You have reached the maximum number of tunnels. Please delete one and try again.",
+            ),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("AccountExpired", "Su cuenta ha caducado."),
+            ("BadRequest", "La solicitud no es válida."),
+            (
+                "InternalError",
+                "Se produjo un error interno del servidor. Vuelva a intentarlo más tarde.",
+            ),
+            ("MissingOrInvalidAuthToken", "La autenticación ha fallado."),
+            ("NoApiRoute", "Esta función no está disponible. Actualice la aplicación."),
+            (
+                "NoLongerSupported",
+                "Esta versión de la aplicación ya no es compatible. Actualice la aplicación.",
+            ),
+            ("NoMatchingExit", "No hay ningún servidor disponible para la ubicación seleccionada."),
+            ("RateLimitExceeded", "Demasiadas solicitudes. Vuelva a intentarlo más tarde."),
+            (
+                "SignupLimitExceeded",
+                "Se han creado demasiadas cuentas recientemente. Vuelva a intentarlo más tarde.",
+            ),
+            (
+                "TunnelLimitExceeded",
+                "Ha alcanzado el número máximo de túneles. Elimine uno y vuelva a intentarlo.",
+            ),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("AccountExpired", "Votre compte a expiré."),
+            ("BadRequest", "La requête n'est pas valide."),
+            (
+                "InternalError",
+                "Une erreur interne du serveur s'est produite. Veuillez réessayer plus tard.",
+            ),
+            ("MissingOrInvalidAuthToken", "L'authentification a échoué."),
+            (
+                "NoApiRoute",
+                "Cette fonctionnalité n'est pas disponible. Veuillez mettre à jour l'application.",
+            ),
+            (
+                "NoLongerSupported",
+                "Cette version de l'application n'est plus prise en charge. Veuillez la mettre à jour.",
+            ),
+            ("NoMatchingExit", "Aucun serveur n'est disponible pour l'emplacement sélectionné."),
+            ("RateLimitExceeded", "Trop de requêtes. Veuillez réessayer plus tard."),
+            (
+                "SignupLimitExceeded",
+                "Trop de comptes ont été créés récemment. Veuillez réessayer plus tard.",
+            ),
+            (
+                "TunnelLimitExceeded",
+                "Vous avez atteint le nombre maximal de tunnels. Veuillez en supprimer un et réessayer.",
+            ),
+        ],
+    ),
+];
+
+/// Languages with bundled error messages.
+pub fn languages() -> impl Iterator<Item = &'static str> {
+    ERROR_MESSAGES.iter().map(|(language, _)| *language)
+}
+
+/// The bundled message for `error` in `language`, which is matched by its primary subtag.
+pub fn error_message(error: &ApiErrorKind, language: &str) -> Option<&'static str> {
+    let name = error.name()?;
+    let language = primary_language(language);
+    let (_, messages) = ERROR_MESSAGES.iter().find(|(l, _)| l.eq_ignore_ascii_case(language))?;
+    messages.iter().find(|(n, _)| *n == name).map(|(_, message)| *message)
+}
+
+impl ApiError {
+    /// The error message in the most preferred language available.
+    ///
+    /// The server's message is preferred if the user accepts its [`content_language`](ApiError::content_language), since it may be more specific than the bundled message. Without a `Content-Language`, the bundled messages are tried first.
+    pub fn localized_msg(&self, accept_language: &AcceptLanguage) -> &str {
+        let server_languages: Vec<&str> = match &self.content_language {
+            Some(languages) => languages.split(',').map(|language| primary_language(language.trim())).collect(),
+            None => Vec::new(),
+        };
+        for language in accept_language.languages() {
+            let language = primary_language(language);
+            if server_languages.iter().any(|server| server.eq_ignore_ascii_case(language)) {
+                break;
+            }
+            if let Some(message) = error_message(&self.body.error, language) {
+                return message;
+            }
+        }
+        &self.body.msg
+    }
+}
+
+#[test]
+fn test_accept_language() {
+    let accept_language = AcceptLanguage::new(["de-CH", "de", "not a tag", "en"]);
+    assert_eq!(accept_language.languages().collect::<Vec<_>>(), ["de-CH", "de", "en"]);
+    assert_eq!(accept_language.to_header_value().unwrap(), "de-CH, de;q=0.9, en;q=0.8");
+    assert_eq!(AcceptLanguage::new(Vec::<String>::new()).to_header_value(), None);
+
+    let many = AcceptLanguage::new((0..12).map(|i| format!("x-{i}")));
+    assert!(many.to_string().ends_with("x-9;q=0.1, x-10;q=0.001, x-11;q=0.001"));
+}
+
+#[test]
+fn test_localized_msg() {
+    let error = ApiError::new(http::StatusCode::NOT_FOUND, ApiErrorKind::NoMatchingExit {}, "No exit in Atlantis.").with_content_language("en");
+    assert_eq!(
+        error.localized_msg(&AcceptLanguage::new(["fr-CA", "en"])),
+        "Aucun serveur n'est disponible pour l'emplacement sélectionné."
+    );
+    assert_eq!(error.localized_msg(&AcceptLanguage::new(["en-GB", "fr"])), "No exit in Atlantis.");
+    assert_eq!(
+        error.localized_msg(&AcceptLanguage::new(["ja", "de"])),
+        "Für den gewählten Standort ist kein Server verfügbar."
+    );
+    assert_eq!(error.localized_msg(&AcceptLanguage::new(["ja"])), "No exit in Atlantis.");
+
+    // A reply which the server already translated is kept.
+    let translated =
+        ApiError::new(http::StatusCode::NOT_FOUND, ApiErrorKind::NoMatchingExit {}, "Kein Server in Atlantis.").with_content_language("de-CH");
+    assert_eq!(translated.localized_msg(&AcceptLanguage::new(["de", "fr"])), "Kein Server in Atlantis.");
+    assert_eq!(
+        translated.localized_msg(&AcceptLanguage::new(["fr", "de"])),
+        "Aucun serveur n'est disponible pour l'emplacement sélectionné."
+    );
+    let unlabeled = ApiError::new(http::StatusCode::NOT_FOUND, ApiErrorKind::NoMatchingExit {}, "No exit in Atlantis.");
+    assert_eq!(
+        unlabeled.localized_msg(&AcceptLanguage::new(["en"])),
+        "No server is available for the selected location."
+    );
+
+    let unknown = ApiError::new(
+        http::StatusCode::BAD_REQUEST,
+        ApiErrorKind::Unknown("FutureError".into()),
+        "Something new.",
+    );
+    assert_eq!(unknown.localized_msg(&AcceptLanguage::new(["de"])), "Something new.");
+}
+
+#[test]
+fn test_catalog_complete() {
+    let kinds = [
+        ApiErrorKind::AccountExpired { expired_at: None },
+        ApiErrorKind::BadRequest {},
+        ApiErrorKind::InternalError {},
+        ApiErrorKind::MissingOrInvalidAuthToken {},
+        ApiErrorKind::NoApiRoute {},
+        ApiErrorKind::NoLongerSupported {},
+        ApiErrorKind::NoMatchingExit {},
+        ApiErrorKind::RateLimitExceeded { reset_at: None },
+        ApiErrorKind::SignupLimitExceeded { reset_at: None },
+        ApiErrorKind::TunnelLimitExceeded {
+            tunnel_count: None,
+            tunnel_limit: None,
+        },
+    ];
+    for (language, messages) in ERROR_MESSAGES {
+        assert_eq!(messages.len(), kinds.len(), "{language}");
+        for kind in &kinds {
+            assert!(error_message(kind, language).is_some(), "{language} {kind:?}");
+        }
+    }
+}
@@ -1,14 +1,33 @@
 #![allow(clippy::redundant_closure)]
 
+pub mod account;
+pub mod catalog;
 pub mod check;
 pub mod cmd;
+pub mod country;
+pub mod geo;
+pub mod l10n;
+pub mod pairing;
+pub mod pricing;
+#[cfg(feature = "probe")]
+pub mod probe;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(test)]
+mod test_support;
 pub mod token;
+pub mod tunnel;
 pub mod types;
+#[cfg(feature = "typescript")]
+pub mod typescript;
+#[cfg(feature = "client")]
+pub mod versioned;
+#[cfg(feature = "keys")]
 pub mod wg_conf;
 
 #[cfg(feature = "client")]
 mod client;
-#[cfg(feature = "client")]
+#[cfg(feature = "notices")]
 pub mod notices;
 #[cfg(feature = "client")]
 pub use client::Client;
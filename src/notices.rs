@@ -1,9 +1,12 @@
 use std::convert::TryFrom;
+use std::sync::Mutex;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use itertools::Itertools as _;
 use serde::{Deserialize, Serialize};
 
+use crate::l10n::AcceptLanguage;
+
 #[derive(Serialize, Deserialize, Debug)]
 struct NoticeResp {
     notices: Vec<NoticeRaw>,
@@ -46,6 +49,7 @@ impl TryFrom<NoticeRaw> for Notice {
 }
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "content")]
 pub enum NoticeDisplay {
     Warn(String),
@@ -55,6 +59,7 @@ pub enum NoticeDisplay {
 pub struct NoticesClient {
     client: reqwest::Client,
     full_url: String,
+    accept_language: Mutex<Option<AcceptLanguage>>,
 }
 
 const NOTICES_PATH_FROM_BASE: &str = "notices";
@@ -71,11 +76,21 @@ impl NoticesClient {
         Self {
             client: reqwest::Client::new(),
             full_url,
+            accept_language: Mutex::new(None),
         }
     }
 
+    /// Sets the languages sent in the `Accept-Language` header.
+    pub fn set_accept_language(&self, accept_language: Option<AcceptLanguage>) {
+        *self.accept_language.lock().unwrap() = accept_language
+    }
+
     pub async fn current_notices(&self, version: &semver::Version) -> anyhow::Result<Vec<NoticeDisplay>> {
-        let resp = self.client.get(&self.full_url).send().await?;
+        let mut req = self.client.get(&self.full_url);
+        if let Some(accept_language) = self.accept_language.lock().unwrap().as_ref().and_then(|a| a.to_header_value()) {
+            req = req.header(http::header::ACCEPT_LANGUAGE, accept_language);
+        }
+        let resp = req.send().await?;
         if let Err(error) = resp.error_for_status_ref() {
             if let Some(reqwest::StatusCode::NOT_FOUND) = error.status() {
                 // 404 means no notices
@@ -111,3 +126,37 @@ impl Notice {
         None
     }
 }
+
+#[test]
+fn test_into_display() {
+    let version: semver::Version = "1.2.3".parse().unwrap();
+    let notice = |version_req: Option<&str>, warn_at: Option<u64>, error_at: Option<u64>| {
+        Notice::try_from(NoticeRaw {
+            message: "Please update".into(),
+            version_req: version_req.map(Into::into),
+            warn_at,
+            error_at,
+        })
+        .unwrap()
+    };
+    let far_future = u32::MAX.into();
+
+    assert!(matches!(notice(None, Some(0), None).into_display(&version), Some(NoticeDisplay::Warn(_))));
+    assert!(matches!(
+        notice(None, Some(0), Some(0)).into_display(&version),
+        Some(NoticeDisplay::Error(_))
+    ));
+    assert!(matches!(
+        notice(Some("<1.3"), Some(0), Some(far_future)).into_display(&version),
+        Some(NoticeDisplay::Warn(_))
+    ));
+    assert!(notice(Some(">=1.3"), Some(0), Some(0)).into_display(&version).is_none());
+    assert!(notice(None, Some(far_future), None).into_display(&version).is_none());
+    assert!(Notice::try_from(NoticeRaw {
+        message: String::new(),
+        version_req: Some("not a version".into()),
+        warn_at: None,
+        error_at: None,
+    })
+    .is_err());
+}
@@ -0,0 +1,228 @@
+//! Choosing the relay and exit for [`CreateTunnel`](crate::cmd::CreateTunnel).
+//!
+//! Relays declare [`OneRelay::preferred_exits`], which are the exits they are best connected to. [`plan_pairings`] combines these with the user's constraints and optional relay latencies into an ordered list of candidates, each explaining why it was ranked where it is.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use crate::catalog::ExitFilter;
+use crate::types::{OneExit, OneRelay};
+
+/// The user's constraints on a tunnel. Unset fields allow any relay or exit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PairingConstraints {
+    pub relay_id: Option<String>,
+    pub exit_id: Option<String>,
+    /// Location of the exit.
+    pub exit_filter: ExitFilter,
+}
+
+/// Why a candidate was ranked where it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PairingReason {
+    /// The exit is one of the relay's preferred exits.
+    PreferredExit,
+    /// The exit isn't one of the relay's preferred exits.
+    NotPreferredExit,
+    RequestedRelay,
+    RequestedExit,
+    /// The exit is in the requested location.
+    RequestedLocation,
+    /// The measured round-trip time to the relay.
+    RelayLatency(Duration),
+    /// Latencies were measured, but not for this relay, which is likely unreachable.
+    RelayNotReachable,
+}
+
+impl Display for PairingReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PairingReason::PreferredExit => f.write_str("exit is preferred by the relay"),
+            PairingReason::NotPreferredExit => f.write_str("exit is not preferred by the relay"),
+            PairingReason::RequestedRelay => f.write_str("relay was requested"),
+            PairingReason::RequestedExit => f.write_str("exit was requested"),
+            PairingReason::RequestedLocation => f.write_str("exit is in the requested location"),
+            PairingReason::RelayLatency(rtt) => write!(f, "relay round-trip time is {} ms", rtt.as_millis()),
+            PairingReason::RelayNotReachable => f.write_str("relay did not respond to latency probes"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PairingCandidate<'a> {
+    pub relay: &'a OneRelay,
+    pub exit: &'a OneExit,
+    pub reasons: Vec<PairingReason>,
+}
+
+impl PairingCandidate<'_> {
+    pub fn is_preferred(&self) -> bool {
+        self.relay.preferred_exits.iter().any(|preferred| preferred.id == self.exit.id)
+    }
+
+    /// The reasons as a single sentence fragment, for logs and debug views.
+    pub fn explanation(&self) -> String {
+        self.reasons.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// All `(relay, exit)` pairs allowed by `constraints`, best first.
+///
+/// Reachable relays rank first, then pairs the relay declares as preferred, then faster relays. Relays without a latency only count as unreachable if `latencies` contains other relays. Ties keep the order of `relays` and, for preferred exits, the relay's order of preference.
+pub fn plan_pairings<'a>(
+    relays: &'a [OneRelay],
+    exits: &'a [OneExit],
+    constraints: &PairingConstraints,
+    latencies: Option<&HashMap<String, Duration>>,
+) -> Vec<PairingCandidate<'a>> {
+    let latencies = latencies.filter(|latencies| !latencies.is_empty());
+    let exits: Vec<&OneExit> = exits
+        .iter()
+        .filter(|exit| constraints.exit_id.as_ref().map_or(true, |id| *id == exit.id) && constraints.exit_filter.matches(exit))
+        .collect();
+
+    let mut ranked = Vec::new();
+    for (relay_index, relay) in relays.iter().enumerate() {
+        if constraints.relay_id.as_ref().is_some_and(|id| *id != relay.id) {
+            continue;
+        }
+        let latency = latencies.map(|latencies| latencies.get(&relay.id).copied());
+        for (exit_index, &exit) in exits.iter().enumerate() {
+            let preference = relay.preferred_exits.iter().position(|preferred| preferred.id == exit.id);
+
+            let mut reasons = vec![match preference {
+                Some(_) => PairingReason::PreferredExit,
+                None => PairingReason::NotPreferredExit,
+            }];
+            if constraints.relay_id.is_some() {
+                reasons.push(PairingReason::RequestedRelay);
+            }
+            if constraints.exit_id.is_some() {
+                reasons.push(PairingReason::RequestedExit);
+            }
+            if constraints.exit_filter != ExitFilter::default() {
+                reasons.push(PairingReason::RequestedLocation);
+            }
+            match latency {
+                Some(Some(rtt)) => reasons.push(PairingReason::RelayLatency(rtt)),
+                Some(None) => reasons.push(PairingReason::RelayNotReachable),
+                None => {}
+            }
+
+            let key = RankKey {
+                unreachable: latency == Some(None),
+                not_preferred: preference.is_none(),
+                rtt: latency.flatten().unwrap_or(Duration::MAX),
+                relay_index,
+                exit_index: preference.unwrap_or(exit_index),
+            };
+            ranked.push((key, PairingCandidate { relay, exit, reasons }));
+        }
+    }
+    ranked.sort_by(|(a, _), (b, _)| a.cmp(b));
+    ranked.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Sort key of a candidate, compared field by field.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct RankKey {
+    unreachable: bool,
+    not_preferred: bool,
+    rtt: Duration,
+    relay_index: usize,
+    exit_index: usize,
+}
+
+#[cfg(test)]
+fn test_network() -> (Vec<OneRelay>, Vec<OneExit>) {
+    use crate::test_support::relay;
+
+    let relay = |id: &str, preferred: &[&str]| relay(id, std::net::Ipv4Addr::LOCALHOST, preferred);
+    let exit = |id: &str, country_code: &str| crate::test_support::exit(id, country_code, id, id);
+    (
+        vec![relay("relay-us", &["nyc", "sfo"]), relay("relay-eu", &["zrh"])],
+        vec![exit("sfo", "US"), exit("nyc", "US"), exit("zrh", "CH")],
+    )
+}
+
+#[cfg(test)]
+fn pairs<'a>(candidates: &[PairingCandidate<'a>]) -> Vec<(&'a str, &'a str)> {
+    candidates.iter().map(|c| (c.relay.id.as_str(), c.exit.id.as_str())).collect()
+}
+
+#[test]
+fn test_preferred_pairings() {
+    let (relays, exits) = test_network();
+    let candidates = plan_pairings(&relays, &exits, &PairingConstraints::default(), None);
+    assert_eq!(
+        pairs(&candidates),
+        [
+            ("relay-us", "nyc"),
+            ("relay-us", "sfo"),
+            ("relay-eu", "zrh"),
+            ("relay-us", "zrh"),
+            ("relay-eu", "sfo"),
+            ("relay-eu", "nyc"),
+        ]
+    );
+    assert!(candidates[0].is_preferred());
+    assert_eq!(candidates[0].explanation(), "exit is preferred by the relay");
+
+    let constraints = PairingConstraints {
+        exit_filter: ExitFilter::country("CH"),
+        ..Default::default()
+    };
+    let candidates = plan_pairings(&relays, &exits, &constraints, None);
+    assert_eq!(pairs(&candidates), [("relay-eu", "zrh"), ("relay-us", "zrh")]);
+    assert_eq!(
+        candidates[1].explanation(),
+        "exit is not preferred by the relay, exit is in the requested location"
+    );
+
+    let constraints = PairingConstraints {
+        relay_id: Some("relay-eu".into()),
+        exit_id: Some("nyc".into()),
+        ..Default::default()
+    };
+    let candidates = plan_pairings(&relays, &exits, &constraints, None);
+    assert_eq!(pairs(&candidates), [("relay-eu", "nyc")]);
+    assert_eq!(
+        candidates[0].reasons,
+        [
+            PairingReason::NotPreferredExit,
+            PairingReason::RequestedRelay,
+            PairingReason::RequestedExit
+        ]
+    );
+}
+
+#[test]
+fn test_latency_ranking() {
+    let (relays, exits) = test_network();
+    let constraints = PairingConstraints {
+        exit_filter: ExitFilter::country("US"),
+        ..Default::default()
+    };
+
+    let latencies = HashMap::from([
+        ("relay-us".to_string(), Duration::from_millis(80)),
+        ("relay-eu".to_string(), Duration::from_millis(10)),
+    ]);
+    let candidates = plan_pairings(&relays, &exits, &constraints, Some(&latencies));
+    assert_eq!(
+        pairs(&candidates),
+        [("relay-us", "nyc"), ("relay-us", "sfo"), ("relay-eu", "sfo"), ("relay-eu", "nyc")]
+    );
+    assert!(candidates[2].reasons.contains(&PairingReason::RelayLatency(Duration::from_millis(10))));
+
+    let latencies = HashMap::from([("relay-eu".to_string(), Duration::from_millis(10))]);
+    let candidates = plan_pairings(&relays, &exits, &constraints, Some(&latencies));
+    assert_eq!(pairs(&candidates)[0], ("relay-eu", "sfo"));
+    assert!(candidates[2].reasons.contains(&PairingReason::RelayNotReachable));
+    assert!(candidates[2].explanation().ends_with("relay did not respond to latency probes"));
+
+    let empty = HashMap::new();
+    let candidates = plan_pairings(&relays, &exits, &constraints, Some(&empty));
+    assert_eq!(pairs(&candidates)[0], ("relay-us", "nyc"));
+}
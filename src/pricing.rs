@@ -0,0 +1,254 @@
+//! Exact money arithmetic and comparisons of [`Price`] plans.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::types::{Price, Prices};
+
+/// An amount of US dollars, stored as whole cents so arithmetic is exact.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money {
+    cents: i64,
+}
+
+impl Money {
+    pub const ZERO: Money = Money { cents: 0 };
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Self { cents }
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.cents
+    }
+
+    /// Divides the amount, rounding half-way cases away from zero.
+    ///
+    /// # Panics
+    ///
+    /// If `divisor` is zero.
+    pub fn div_round(self, divisor: i64) -> Money {
+        let quotient = self.cents / divisor;
+        let remainder = self.cents % divisor;
+        let round_away = remainder.unsigned_abs() * 2 >= divisor.unsigned_abs();
+        let away = if (self.cents < 0) == (divisor < 0) { 1 } else { -1 };
+        Money::from_cents(quotient + if round_away { away } else { 0 })
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money::from_cents(self.cents + rhs.cents)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money::from_cents(self.cents - rhs.cents)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::from_cents(-self.cents)
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: i64) -> Money {
+        Money::from_cents(self.cents * rhs)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+/// Formats as US dollars with thousands separators, for example "$1,234.50" or "-$0.99".
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.cents < 0 {
+            f.write_str("-")?;
+        }
+        let cents = self.cents.unsigned_abs();
+        let dollars = (cents / 100).to_string();
+        f.write_str("$")?;
+        for (i, digit) in dollars.chars().enumerate() {
+            if i > 0 && (dollars.len() - i) % 3 == 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{digit}")?;
+        }
+        write!(f, ".{:02}", cents % 100)
+    }
+}
+
+/// Percentage by which `price` is below `reference`, rounded down so savings are never overstated.
+fn discount_percent(price: Money, reference: Money) -> u32 {
+    if reference.cents <= 0 || price >= reference {
+        return 0;
+    }
+    ((reference - price).cents * 100 / reference.cents) as u32
+}
+
+impl Price {
+    pub fn price(&self) -> Money {
+        Money::from_cents(self.usd_cents.into())
+    }
+
+    pub fn regular_price(&self) -> Money {
+        Money::from_cents(self.regular_usd_cents.into())
+    }
+
+    fn months(&self) -> i64 {
+        i64::from(self.months.max(1))
+    }
+
+    /// The price per month, rounded to the nearest cent.
+    pub fn per_month(&self) -> Money {
+        self.price().div_round(self.months())
+    }
+
+    /// How much cheaper this plan is than its regular price.
+    pub fn savings(&self) -> Money {
+        (self.regular_price() - self.price()).max(Money::ZERO)
+    }
+
+    pub fn discount_percent(&self) -> u32 {
+        discount_percent(self.price(), self.regular_price())
+    }
+
+    /// How much cheaper this plan is than paying for `monthly` for the same number of months.
+    pub fn savings_vs(&self, monthly: &Price) -> Money {
+        (self.cost_of_months(monthly) - self.price()).max(Money::ZERO)
+    }
+
+    pub fn discount_percent_vs(&self, monthly: &Price) -> u32 {
+        discount_percent(self.price(), self.cost_of_months(monthly))
+    }
+
+    fn cost_of_months(&self, monthly: &Price) -> Money {
+        // `monthly` may itself span several months, so compare exact totals instead of rounded monthly prices.
+        (monthly.price() * self.months()).div_round(monthly.months())
+    }
+
+    /// Compares the exact price per month of two plans.
+    pub fn cmp_per_month(&self, other: &Price) -> Ordering {
+        (self.price().cents * other.months()).cmp(&(other.price().cents * self.months()))
+    }
+}
+
+/// The one month plan, which other plans are usually compared against.
+pub fn monthly_plan(plans: &[Price]) -> Option<&Price> {
+    plans.iter().find(|plan| plan.months == 1)
+}
+
+/// Plans ordered from the lowest to the highest price per month.
+///
+/// Plans with the same price per month keep their order.
+pub fn rank_by_value(plans: &[Price]) -> Vec<&Price> {
+    let mut ranked: Vec<&Price> = plans.iter().collect();
+    ranked.sort_by(|a, b| a.cmp_per_month(b));
+    ranked
+}
+
+/// The plan with the lowest price per month, if it is cheaper per month than any other plan.
+///
+/// Suitable for a "best value" badge, which shouldn't be shown if several plans are equally good.
+pub fn best_value(plans: &[Price]) -> Option<&Price> {
+    match rank_by_value(plans)[..] {
+        [best] => Some(best),
+        [best, second, ..] if best.cmp_per_month(second) == Ordering::Less => Some(best),
+        _ => None,
+    }
+}
+
+impl Prices {
+    pub fn monthly_subscription(&self) -> Option<&Price> {
+        monthly_plan(&self.subscription)
+    }
+
+    pub fn monthly_top_up(&self) -> Option<&Price> {
+        monthly_plan(&self.top_up)
+    }
+
+    pub fn subscription_by_value(&self) -> Vec<&Price> {
+        rank_by_value(&self.subscription)
+    }
+
+    pub fn top_up_by_value(&self) -> Vec<&Price> {
+        rank_by_value(&self.top_up)
+    }
+
+    pub fn best_value_subscription(&self) -> Option<&Price> {
+        best_value(&self.subscription)
+    }
+
+    pub fn best_value_top_up(&self) -> Option<&Price> {
+        best_value(&self.top_up)
+    }
+}
+
+#[test]
+fn test_money() {
+    assert_eq!(Money::from_cents(800).to_string(), "$8.00");
+    assert_eq!(Money::from_cents(123456789).to_string(), "$1,234,567.89");
+    assert_eq!(Money::from_cents(-99).to_string(), "-$0.99");
+    assert_eq!(Money::from_cents(100000).to_string(), "$1,000.00");
+    assert_eq!(Money::from_cents(8000).div_round(12), Money::from_cents(667));
+    assert_eq!(Money::from_cents(1000).div_round(8), Money::from_cents(125));
+    assert_eq!(Money::from_cents(5).div_round(2), Money::from_cents(3));
+    assert_eq!(Money::from_cents(-5).div_round(2), Money::from_cents(-3));
+    assert_eq!(Money::from_cents(10).div_round(3), Money::from_cents(3));
+    assert_eq!(
+        [Money::from_cents(1), Money::from_cents(2)].into_iter().sum::<Money>() * 3 - Money::from_cents(10),
+        -Money::from_cents(1)
+    );
+}
+
+#[test]
+fn test_price_analytics() {
+    let prices: Prices = serde_json::from_str(
+        r#"{
+        "subscription": [
+            {"months": 1, "usd_cents": 800, "regular_usd_cents": 800, "sale": null},
+            {"months": 12, "usd_cents": 8000, "regular_usd_cents": 9600, "sale": null},
+            {"months": 6, "usd_cents": 4200, "regular_usd_cents": 4800, "sale": null}
+        ],
+        "top_up": [
+            {"months": 1, "usd_cents": 800, "regular_usd_cents": 800, "sale": null},
+            {"months": 2, "usd_cents": 1600, "regular_usd_cents": 1600, "sale": null}
+        ],
+        "sale": null
+    }"#,
+    )
+    .unwrap();
+
+    let monthly = prices.monthly_subscription().unwrap();
+    let yearly = &prices.subscription[1];
+    assert_eq!(yearly.per_month(), Money::from_cents(667));
+    assert_eq!(yearly.savings(), Money::from_cents(1600));
+    assert_eq!(yearly.discount_percent(), 16);
+    assert_eq!(yearly.savings_vs(monthly), Money::from_cents(1600));
+    assert_eq!(yearly.discount_percent_vs(monthly), 16);
+    assert_eq!(monthly.savings_vs(monthly), Money::ZERO);
+    assert_eq!(prices.subscription[2].discount_percent_vs(monthly), 12);
+
+    let ranked: Vec<u16> = prices.subscription_by_value().iter().map(|p| p.months).collect();
+    assert_eq!(ranked, [12, 6, 1]);
+    assert_eq!(prices.best_value_subscription().map(|p| p.months), Some(12));
+    assert!(prices.best_value_top_up().is_none());
+    assert!(best_value(&[]).is_none());
+}
@@ -0,0 +1,263 @@
+//! Measuring round-trip times to relays.
+//!
+//! [`probe_relays`] ranks relays by latency from the user's network, so the fastest one can be passed as the `relay` of [`CreateTunnel`](crate::cmd::CreateTunnel).
+//!
+//! ```no_run
+//! # async fn example(relays: Vec<obscuravpn_api::types::OneRelay>) {
+//! use obscuravpn_api::probe::{probe_relays, ProbeConfig};
+//!
+//! let ranking = probe_relays(relays, &ProbeConfig::default()).await;
+//! let fastest = ranking.iter().find(|latency| latency.rtt.is_ok()).map(|latency| latency.relay.id.clone());
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::{timeout, Instant};
+
+use crate::types::OneRelay;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpFamily {
+    V4,
+    V6,
+}
+
+/// How a single round trip is measured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProbeMethod {
+    /// Time to complete a TCP handshake.
+    TcpConnect { port: u16 },
+    /// Time until any datagram is received in response to `payload`.
+    UdpEcho { port: u16, payload: Vec<u8> },
+}
+
+impl ProbeMethod {
+    fn port(&self) -> u16 {
+        match self {
+            ProbeMethod::TcpConnect { port } | ProbeMethod::UdpEcho { port, .. } => *port,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProbeConfig {
+    pub method: ProbeMethod,
+    pub family: IpFamily,
+    /// Timeout of each attempt.
+    pub timeout: Duration,
+    /// Number of round trips per relay. The fastest one counts, to smooth over packet loss and scheduling noise.
+    pub attempts: u32,
+    /// Maximum number of relays probed at once.
+    pub concurrency: usize,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            method: ProbeMethod::TcpConnect { port: 443 },
+            family: IpFamily::V4,
+            timeout: Duration::from_secs(2),
+            attempts: 3,
+            concurrency: 8,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ProbeError {
+    #[error("no response within the timeout")]
+    Timeout,
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug)]
+pub struct RelayLatency {
+    pub relay: OneRelay,
+    pub addr: SocketAddr,
+    /// The fastest round-trip time, or the error of the last attempt if none succeeded.
+    pub rtt: Result<Duration, ProbeError>,
+}
+
+/// Probes all `relays` and returns them ordered from the fastest to the slowest.
+///
+/// Relays which couldn't be reached come last, in their original order.
+pub async fn probe_relays(relays: impl IntoIterator<Item = OneRelay>, config: &ProbeConfig) -> Vec<RelayLatency> {
+    let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for (i, relay) in relays.into_iter().enumerate() {
+        let ip = match config.family {
+            IpFamily::V4 => IpAddr::V4(relay.ip_v4),
+            IpFamily::V6 => IpAddr::V6(relay.ip_v6),
+        };
+        let addr = SocketAddr::new(ip, config.method.port());
+        let semaphore = semaphore.clone();
+        let config = config.clone();
+        tasks.spawn(async move {
+            // The semaphore is never closed.
+            let _permit = semaphore.acquire_owned().await.unwrap();
+            let rtt = probe(addr, &config).await;
+            (i, RelayLatency { relay, addr, rtt })
+        });
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(result) => results.push(result),
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+    rank(results)
+}
+
+/// Orders latencies from the fastest to the slowest, keeping the input order of unreachable relays and ties.
+fn rank(mut results: Vec<(usize, RelayLatency)>) -> Vec<RelayLatency> {
+    results.sort_by_key(|(i, _)| *i);
+    let mut ranking: Vec<RelayLatency> = results.into_iter().map(|(_, latency)| latency).collect();
+    ranking.sort_by_key(|latency| latency.rtt.as_ref().ok().copied().unwrap_or(Duration::MAX));
+    ranking
+}
+
+/// Round-trip times of the reachable relays by relay ID, as used by [`plan_pairings`](crate::pairing::plan_pairings).
+pub fn relay_latencies(ranking: &[RelayLatency]) -> HashMap<String, Duration> {
+    ranking
+        .iter()
+        .filter_map(|latency| Some((latency.relay.id.clone(), *latency.rtt.as_ref().ok()?)))
+        .collect()
+}
+
+/// Measures the fastest round trip to `addr` over `config.attempts` attempts.
+pub async fn probe(addr: SocketAddr, config: &ProbeConfig) -> Result<Duration, ProbeError> {
+    let mut best: Option<Duration> = None;
+    let mut last_err = ProbeError::Timeout;
+    for _ in 0..config.attempts.max(1) {
+        match round_trip(addr, config).await {
+            Ok(rtt) => best = Some(best.map_or(rtt, |best| best.min(rtt))),
+            Err(err) => last_err = err,
+        }
+    }
+    best.ok_or(last_err)
+}
+
+async fn round_trip(addr: SocketAddr, config: &ProbeConfig) -> Result<Duration, ProbeError> {
+    match &config.method {
+        ProbeMethod::TcpConnect { .. } => {
+            let start = Instant::now();
+            timeout(config.timeout, TcpStream::connect(addr))
+                .await
+                .map_err(|_| ProbeError::Timeout)??;
+            Ok(start.elapsed())
+        }
+        ProbeMethod::UdpEcho { payload, .. } => {
+            let local: IpAddr = match addr {
+                SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+                SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+            };
+            let socket = UdpSocket::bind((local, 0)).await?;
+            socket.connect(addr).await?;
+            let start = Instant::now();
+            socket.send(payload).await?;
+            let mut buf = [0; 1500];
+            timeout(config.timeout, socket.recv(&mut buf)).await.map_err(|_| ProbeError::Timeout)??;
+            Ok(start.elapsed())
+        }
+    }
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_probe_udp() {
+    let echo = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let port = echo.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let mut buf = [0; 1500];
+        loop {
+            let (n, peer) = echo.recv_from(&mut buf).await.unwrap();
+            echo.send_to(&buf[..n], peer).await.unwrap();
+        }
+    });
+    let closed = UdpSocket::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+    let config = ProbeConfig {
+        method: ProbeMethod::UdpEcho {
+            port,
+            payload: b"ping".to_vec(),
+        },
+        timeout: Duration::from_millis(200),
+        attempts: 2,
+        ..Default::default()
+    };
+    let ranking = probe_relays([crate::test_support::relay("local", Ipv4Addr::LOCALHOST, &[])], &config).await;
+    assert_eq!(ranking[0].addr, SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+    assert!(ranking[0].rtt.is_ok());
+    assert!(probe(closed, &config).await.is_err());
+}
+
+#[test]
+fn test_rank() {
+    let results = [
+        ("unreachable-a", Err(ProbeError::Timeout)),
+        ("slow", Ok(Duration::from_millis(30))),
+        ("unreachable-b", Err(ProbeError::Timeout)),
+        ("fast", Ok(Duration::from_millis(10))),
+    ];
+    // Probes finish in any order.
+    let results = results
+        .into_iter()
+        .enumerate()
+        .rev()
+        .map(|(i, (id, rtt))| {
+            let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 443));
+            (
+                i,
+                RelayLatency {
+                    relay: crate::test_support::relay(id, Ipv4Addr::LOCALHOST, &[]),
+                    addr,
+                    rtt,
+                },
+            )
+        })
+        .collect();
+    let ranking = rank(results);
+    let ids: Vec<_> = ranking.iter().map(|latency| latency.relay.id.as_str()).collect();
+    assert_eq!(ids, ["fast", "slow", "unreachable-a", "unreachable-b"]);
+    assert_eq!(relay_latencies(&ranking).len(), 2);
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_probe_tcp() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let _ = listener.accept().await.unwrap();
+        }
+    });
+
+    let config = ProbeConfig {
+        method: ProbeMethod::TcpConnect { port },
+        ..Default::default()
+    };
+    let ranking = probe_relays([crate::test_support::relay("local", Ipv4Addr::LOCALHOST, &[])], &config).await;
+    assert_eq!(ranking[0].addr, SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+    assert!(ranking[0].rtt.is_ok());
+
+    let v6 = ProbeConfig {
+        family: IpFamily::V6,
+        timeout: Duration::from_millis(200),
+        attempts: 1,
+        ..config
+    };
+    let ranking = probe_relays([crate::test_support::relay("local", Ipv4Addr::LOCALHOST, &[])], &v6).await;
+    assert_eq!(ranking[0].addr.ip(), IpAddr::V6(Ipv6Addr::LOCALHOST));
+}
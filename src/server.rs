@@ -0,0 +1,192 @@
+//! Serve the API contract with [`axum`].
+//!
+//! Each [`Cmd`] is routed by its [`Cmd::METHOD`] and [`Cmd::PATH`]. Handlers receive the typed command along with the bearer [`AuthToken`], and return either the command's output or an [`ApiError`].
+//!
+//! ```no_run
+//! use obscuravpn_api::cmd::{ApiError, ListTunnels};
+//! use obscuravpn_api::server::{no_api_route, CmdRequest, CmdRouter as _};
+//! use obscuravpn_api::types::OneTunnel;
+//!
+//! async fn list_tunnels(_state: (), _request: CmdRequest<ListTunnels>) -> Result<Vec<OneTunnel>, ApiError> {
+//!     Ok(vec![])
+//! }
+//!
+//! let router: axum::Router = axum::Router::new().cmd(list_tunnels).fallback(no_api_route);
+//! ```
+
+use std::future::Future;
+
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request, State};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{on, MethodFilter};
+use axum::{Json, Router};
+use http::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::cmd::{ApiError, ApiErrorKind, Cmd};
+use crate::types::AuthToken;
+
+/// Extracts the command and the bearer auth token from a request.
+///
+/// Rejects the request with `MissingOrInvalidAuthToken` if there is no bearer token and with `BadRequest` if the body isn't a valid command.
+#[derive(Debug)]
+pub struct CmdRequest<C: Cmd> {
+    pub auth_token: AuthToken,
+    pub cmd: C,
+}
+
+#[axum::async_trait]
+impl<C: Cmd, S: Send + Sync> FromRequest<S> for CmdRequest<C> {
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_token = bearer_auth_token(req.headers())?;
+        let body = Bytes::from_request(req, state).await.map_err(|err| {
+            ApiError::new(StatusCode::BAD_REQUEST, ApiErrorKind::BadRequest {}, "Failed to read request body.").with_detail(err.to_string())
+        })?;
+        let cmd = parse_cmd(&body)?;
+        Ok(Self { auth_token, cmd })
+    }
+}
+
+fn bearer_auth_token(headers: &http::HeaderMap) -> Result<AuthToken, ApiError> {
+    headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .filter(|token| !token.is_empty())
+        .map(|token| AuthToken::from(token.to_string()))
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                ApiErrorKind::MissingOrInvalidAuthToken {},
+                "Missing or invalid auth token.",
+            )
+        })
+}
+
+// `GET` commands are sent without a body, so they are parsed from the JSON representation of an empty struct or tuple struct.
+fn parse_cmd<C: DeserializeOwned>(body: &[u8]) -> Result<C, ApiError> {
+    let result = if body.is_empty() {
+        serde_json::from_str("{}").or_else(|_| serde_json::from_str("[]"))
+    } else {
+        serde_json::from_slice(body)
+    };
+    result.map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, ApiErrorKind::BadRequest {}, "Invalid request body.").with_detail(err.to_string()))
+}
+
+/// Serializes a command output as a successful JSON response.
+#[derive(Debug)]
+pub struct CmdOutput<T>(pub T);
+
+impl<T: Serialize> IntoResponse for CmdOutput<T> {
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, Json(self.body)).into_response();
+        if let Some(language) = self.content_language.and_then(|language| http::HeaderValue::from_str(&language).ok()) {
+            response.headers_mut().insert(http::header::CONTENT_LANGUAGE, language);
+        }
+        response
+    }
+}
+
+/// Fallback handler responding with `NoApiRoute`, for use with [`Router::fallback`].
+pub async fn no_api_route() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, ApiErrorKind::NoApiRoute {}, "No such API route.")
+}
+
+pub trait CmdRouter<S> {
+    /// Routes the command `C` to `handler`.
+    ///
+    /// The handler is called with a clone of the router state.
+    fn cmd<C, F, Fut>(self, handler: F) -> Self
+    where
+        C: Cmd + Send + 'static,
+        C::Output: Send,
+        F: Fn(S, CmdRequest<C>) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<C::Output, ApiError>> + Send + 'static;
+}
+
+impl<S: Clone + Send + Sync + 'static> CmdRouter<S> for Router<S> {
+    fn cmd<C, F, Fut>(self, handler: F) -> Self
+    where
+        C: Cmd + Send + 'static,
+        C::Output: Send,
+        F: Fn(S, CmdRequest<C>) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<C::Output, ApiError>> + Send + 'static,
+    {
+        let method = MethodFilter::try_from(C::METHOD).unwrap_or_else(|_| panic!("unsupported method {}", C::METHOD));
+        self.route(
+            &format!("/{}", C::PATH),
+            on(method, move |State(state): State<S>, request: CmdRequest<C>| async move {
+                handler(state, request).await.map(CmdOutput)
+            }),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+#[tokio::test]
+async fn test_client_roundtrip() {
+    use crate::cmd::{DeleteTunnel, GetAccountInfo, ListTunnels};
+    use crate::token::AcquireToken;
+    use crate::types::{AccountInfo, OneTunnel};
+    use crate::{Client, ClientError};
+
+    async fn acquire_token(Json(req): Json<AcquireToken>) -> Json<String> {
+        Json(format!("token-{}", req.account_id.as_str()))
+    }
+
+    async fn get_account_info(_: (), request: CmdRequest<GetAccountInfo>) -> Result<AccountInfo, ApiError> {
+        assert_eq!(request.auth_token.as_str(), "token-12345678901234567895");
+        Err(ApiError::new(
+            http::StatusCode::PAYMENT_REQUIRED,
+            ApiErrorKind::AccountExpired { expired_at: None },
+            "Account Expired",
+        ))
+    }
+
+    async fn list_tunnels(_: (), _: CmdRequest<ListTunnels>) -> Result<Vec<OneTunnel>, ApiError> {
+        Ok(vec![])
+    }
+
+    async fn delete_tunnel(_: (), request: CmdRequest<DeleteTunnel>) -> Result<(), ApiError> {
+        assert_eq!(request.cmd.id, "tunnel-id");
+        Ok(())
+    }
+
+    let router = Router::new()
+        .route("/token", axum::routing::post(acquire_token))
+        .cmd(get_account_info)
+        .cmd(list_tunnels)
+        .cmd(delete_tunnel)
+        .fallback(no_api_route);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = Client::new(&base_url, "1234 5678 9012 3456 7895".parse().unwrap(), "test").unwrap();
+    assert!(client.run(ListTunnels {}).await.unwrap().is_empty());
+    client.run(DeleteTunnel { id: "tunnel-id".into() }).await.unwrap();
+    match client.run(GetAccountInfo()).await {
+        Err(ClientError::ApiError(err)) => {
+            assert_eq!(err.status, http::StatusCode::PAYMENT_REQUIRED);
+            assert_eq!(err.body.error, ApiErrorKind::AccountExpired { expired_at: None });
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let err = bearer_auth_token(&http::HeaderMap::new()).unwrap_err();
+    assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+    let res = reqwest::get(format!("{base_url}does-not-exist")).await.unwrap();
+    assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+    let err: crate::cmd::ApiErrorBody = res.json().await.unwrap();
+    assert_eq!(err.error, ApiErrorKind::NoApiRoute {});
+}
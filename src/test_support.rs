@@ -0,0 +1,31 @@
+//! Fixtures shared by the tests of several modules.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::types::{OneExit, OneRelay, RelayPreferredExit, TunnelConfig, WgClientConfig, WgPubkey, WgServerConfig};
+
+/// An exit without coordinates.
+pub(crate) fn exit(id: &str, country_code: &str, city_code: &str, city_name: &str) -> OneExit {
+    OneExit::new(id.into(), country_code.into(), city_code.into(), city_name.into())
+}
+
+/// A relay on `ip_v4` and the IPv6 loopback address, preferring the exits with the IDs in `preferred_exits`.
+pub(crate) fn relay(id: &str, ip_v4: Ipv4Addr, preferred_exits: &[&str]) -> OneRelay {
+    let preferred_exits = preferred_exits.iter().map(|id| RelayPreferredExit::new(id.to_string())).collect();
+    OneRelay::new(id.into(), ip_v4, Ipv6Addr::LOCALHOST, preferred_exits)
+}
+
+/// A tunnel with an IPv4 and an IPv6 client address and endpoint.
+pub(crate) fn udp_port_config(client_pubkey: WgPubkey) -> TunnelConfig {
+    TunnelConfig::UdpPort {
+        client: WgClientConfig {
+            wg_pubkey: client_pubkey,
+            addresses: vec!["10.150.177.7/32".parse().unwrap(), "fc00:bbbb:bbbb:bb01:d:0:16:b107/128".parse().unwrap()],
+        },
+        server: WgServerConfig {
+            wg_pubkey: "4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs=".parse().unwrap(),
+            endpoints: vec!["121.127.40.52:23527".parse().unwrap(), "[2001:db8::1]:23527".parse().unwrap()],
+            dnses: vec!["10.64.0.1".parse().unwrap()],
+        },
+    }
+}
@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::types::AccountId;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct AcquireToken {
-    pub account_id: String,
+    pub account_id: AccountId,
 }
 
 impl AcquireToken {
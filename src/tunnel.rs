@@ -0,0 +1,324 @@
+//! Transport-independent view of a tunnel's WireGuard parameters.
+//!
+//! [`TunnelConfig::UdpPort`] and [`TunnelConfig::Obfuscated`] name the same concepts differently. [`TunnelParams`] exposes them under one set of names, so code which only needs the addresses, DNS servers, keys and endpoints doesn't have to match on the transport.
+//!
+//! [`TunnelConfig::validate`] and [`OneTunnel::validate`] check that these parameters are usable before a config is built from them.
+
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+
+use ipnetwork::IpNetwork;
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::types::{OneTunnel, TunnelConfig, WgClientConfig, WgPubkey, WgServerConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TunnelTransport {
+    /// Plain WireGuard over UDP.
+    UdpPort,
+    /// WireGuard carried through a relay.
+    Obfuscated,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TunnelParams<'a> {
+    pub transport: TunnelTransport,
+    pub client_pubkey: &'a WgPubkey,
+    /// Addresses assigned to the client inside the tunnel.
+    pub client_addresses: Vec<IpNetwork>,
+    pub dns: &'a [IpAddr],
+    /// Public key of the WireGuard peer, which is the exit for obfuscated tunnels.
+    pub peer_pubkey: &'a WgPubkey,
+    /// Addresses to connect to, in the order given by the API.
+    pub endpoints: Vec<SocketAddr>,
+}
+
+impl<'a> TunnelParams<'a> {
+    pub fn udp_port(client: &'a WgClientConfig, server: &'a WgServerConfig) -> Self {
+        Self {
+            transport: TunnelTransport::UdpPort,
+            client_pubkey: &client.wg_pubkey,
+            client_addresses: client.addresses.clone(),
+            dns: &server.dnses,
+            peer_pubkey: &server.wg_pubkey,
+            endpoints: server.endpoints.clone(),
+        }
+    }
+
+    pub fn client_ipv4(&self) -> impl Iterator<Item = &IpNetwork> {
+        self.client_addresses.iter().filter(|address| address.is_ipv4())
+    }
+
+    pub fn client_ipv6(&self) -> impl Iterator<Item = &IpNetwork> {
+        self.client_addresses.iter().filter(|address| address.is_ipv6())
+    }
+}
+
+impl TunnelConfig {
+    /// The tunnel's parameters, or `None` if the transport is unknown.
+    pub fn params(&self) -> Option<TunnelParams<'_>> {
+        match self {
+            TunnelConfig::UdpPort { client, server } => Some(TunnelParams::udp_port(client, server)),
+            TunnelConfig::Obfuscated(config) => Some(TunnelParams {
+                transport: TunnelTransport::Obfuscated,
+                client_pubkey: &config.client_pubkey,
+                client_addresses: config
+                    .client_ips_v4
+                    .iter()
+                    .copied()
+                    .map(IpNetwork::from)
+                    .chain(config.client_ips_v6.iter().copied().map(IpNetwork::from))
+                    .collect(),
+                dns: &config.dns,
+                peer_pubkey: &config.exit_pubkey,
+                endpoints: vec![config.relay_addr_v4.into(), config.relay_addr_v6.into()],
+            }),
+            TunnelConfig::Unknown(_) => None,
+        }
+    }
+}
+
+impl OneTunnel {
+    /// The tunnel's parameters, or `None` if the transport is unsupported.
+    pub fn params(&self) -> Option<TunnelParams<'_>> {
+        self.config.params()
+    }
+}
+
+/// A reason why a tunnel can't be connected to as returned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TunnelProblem {
+    UnsupportedTransport,
+    NoEndpoints,
+    NoIpv4Address,
+    NoIpv6Address,
+    /// A DNS server which isn't a private address, so queries to it would not stay inside the tunnel.
+    DnsOutsideTunnel(IpAddr),
+    UnspecifiedEndpoint(SocketAddr),
+    /// The relay's `ip_v4` or `ip_v6` is unspecified.
+    UnspecifiedRelayAddress(IpAddr),
+    /// The obfuscated relay's certificate is not a PEM encoded certificate.
+    InvalidRelayCert,
+}
+
+impl Display for TunnelProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TunnelProblem::UnsupportedTransport => f.write_str("unsupported transport"),
+            TunnelProblem::NoEndpoints => f.write_str("no endpoints"),
+            TunnelProblem::NoIpv4Address => f.write_str("no IPv4 client address"),
+            TunnelProblem::NoIpv6Address => f.write_str("no IPv6 client address"),
+            TunnelProblem::DnsOutsideTunnel(ip) => write!(f, "DNS server {ip} is outside the tunnel range"),
+            TunnelProblem::UnspecifiedEndpoint(addr) => write!(f, "endpoint {addr} is unspecified"),
+            TunnelProblem::UnspecifiedRelayAddress(ip) => write!(f, "relay address {ip} is unspecified"),
+            TunnelProblem::InvalidRelayCert => f.write_str("relay certificate is not a PEM certificate"),
+        }
+    }
+}
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("invalid tunnel: {}", .0.iter().format(", "))]
+pub struct InvalidTunnelError(pub Vec<TunnelProblem>);
+
+impl InvalidTunnelError {
+    fn check(problems: Vec<TunnelProblem>) -> Result<(), Self> {
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Self(problems))
+        }
+    }
+}
+
+impl TunnelParams<'_> {
+    /// Checks the transport-independent parameters, see [`TunnelConfig::validate`].
+    pub fn validate(&self) -> Result<(), InvalidTunnelError> {
+        InvalidTunnelError::check(self.problems())
+    }
+
+    fn problems(&self) -> Vec<TunnelProblem> {
+        let mut problems = Vec::new();
+        if self.endpoints.is_empty() {
+            problems.push(TunnelProblem::NoEndpoints);
+        }
+        for endpoint in &self.endpoints {
+            if endpoint.ip().is_unspecified() {
+                problems.push(TunnelProblem::UnspecifiedEndpoint(*endpoint));
+            }
+        }
+        if self.client_ipv4().next().is_none() {
+            problems.push(TunnelProblem::NoIpv4Address);
+        }
+        if self.client_ipv6().next().is_none() {
+            problems.push(TunnelProblem::NoIpv6Address);
+        }
+        for &dns in self.dns {
+            if !is_tunnel_address(dns) {
+                problems.push(TunnelProblem::DnsOutsideTunnel(dns));
+            }
+        }
+        problems
+    }
+}
+
+/// Whether `ip` is in one of the private ranges which tunnel-internal services are assigned from.
+fn is_tunnel_address(ip: IpAddr) -> bool {
+    match ip {
+        // Private and shared address space (100.64.0.0/10).
+        IpAddr::V4(ip) => ip.is_private() || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64),
+        // Unique local addresses (fc00::/7).
+        IpAddr::V6(ip) => ip.segments()[0] & 0xfe00 == 0xfc00,
+    }
+}
+
+/// Whether `cert` consists of one or more PEM encoded certificates.
+fn is_pem_cert(cert: &str) -> bool {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut rest = cert.trim();
+    if rest.is_empty() {
+        return false;
+    }
+    while !rest.is_empty() {
+        let Some((body, after)) = rest.strip_prefix(BEGIN).and_then(|rest| rest.split_once(END)) else {
+            return false;
+        };
+        let body: String = body.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        // A DER certificate is an ASN.1 SEQUENCE.
+        if STANDARD.decode(body).ok().and_then(|der| der.first().copied()) != Some(0x30) {
+            return false;
+        }
+        rest = after.trim_start();
+    }
+    true
+}
+
+impl TunnelConfig {
+    /// Checks that the tunnel can be connected to, reporting every problem found.
+    pub fn validate(&self) -> Result<(), InvalidTunnelError> {
+        let Some(params) = self.params() else {
+            return Err(InvalidTunnelError(vec![TunnelProblem::UnsupportedTransport]));
+        };
+        let mut problems = params.problems();
+        if let TunnelConfig::Obfuscated(config) = self {
+            if !is_pem_cert(&config.relay_cert) {
+                problems.push(TunnelProblem::InvalidRelayCert);
+            }
+        }
+        InvalidTunnelError::check(problems)
+    }
+}
+
+impl OneTunnel {
+    /// Checks the tunnel's config and relay, see [`TunnelConfig::validate`].
+    pub fn validate(&self) -> Result<(), InvalidTunnelError> {
+        let mut problems = self.config.validate().err().map(|err| err.0).unwrap_or_default();
+        for ip in [IpAddr::V4(self.relay.ip_v4), IpAddr::V6(self.relay.ip_v6)] {
+            if ip.is_unspecified() {
+                problems.push(TunnelProblem::UnspecifiedRelayAddress(ip));
+            }
+        }
+        InvalidTunnelError::check(problems)
+    }
+}
+
+#[cfg(test)]
+const TEST_CERT: &str = "-----BEGIN CERTIFICATE-----\nMAMCAQA=\n-----END CERTIFICATE-----\n";
+
+#[test]
+fn test_params() {
+    let udp_port = crate::test_support::udp_port_config("wjaiHUEOJ8k3X+U3b6H6yTcipqFipIbFQSB0CwZDNlQ=".parse().unwrap());
+    let obfuscated: TunnelConfig = serde_json::from_str(
+        r#"{
+          "type": "obfuscated",
+          "client_pubkey": "wjaiHUEOJ8k3X+U3b6H6yTcipqFipIbFQSB0CwZDNlQ=",
+          "client_ips_v4": ["10.150.177.7/32"],
+          "client_ips_v6": ["fc00:bbbb:bbbb:bb01:d:0:16:b107/128"],
+          "dns": ["10.64.0.1"],
+          "relay_addr_v4": "121.127.40.52:443",
+          "relay_addr_v6": "[2001:db8::1]:443",
+          "relay_cert": "",
+          "exit_pubkey": "4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs="
+        }"#,
+    )
+    .unwrap();
+
+    let udp_port = udp_port.params().unwrap();
+    let obfuscated = obfuscated.params().unwrap();
+    assert_eq!(udp_port.transport, TunnelTransport::UdpPort);
+    assert_eq!(obfuscated.transport, TunnelTransport::Obfuscated);
+    assert_eq!(udp_port.client_pubkey, obfuscated.client_pubkey);
+    assert_eq!(udp_port.client_addresses, obfuscated.client_addresses);
+    assert_eq!(udp_port.dns, obfuscated.dns);
+    assert_eq!(udp_port.peer_pubkey, obfuscated.peer_pubkey);
+    assert_eq!(udp_port.client_ipv4().map(ToString::to_string).collect::<Vec<_>>(), ["10.150.177.7/32"]);
+    assert_eq!(obfuscated.client_ipv6().count(), 1);
+    assert_eq!(
+        obfuscated.endpoints,
+        ["121.127.40.52:443".parse().unwrap(), "[2001:db8::1]:443".parse::<SocketAddr>().unwrap()]
+    );
+
+    assert_eq!(TunnelConfig::Unknown(serde_json::json!({"type": "quic"})).params(), None);
+}
+
+#[test]
+fn test_validate() {
+    let mut config = crate::types::ObfuscatedTunnelConfig {
+        client_pubkey: "wjaiHUEOJ8k3X+U3b6H6yTcipqFipIbFQSB0CwZDNlQ=".parse().unwrap(),
+        client_ips_v4: vec!["10.150.177.7/32".parse().unwrap()],
+        client_ips_v6: vec!["fc00:bbbb:bbbb:bb01:d:0:16:b107/128".parse().unwrap()],
+        dns: vec!["10.64.0.1".parse().unwrap(), "fc00:bbbb:bbbb:bb01::1".parse().unwrap()],
+        relay_addr_v4: "121.127.40.52:443".parse().unwrap(),
+        relay_addr_v6: "[2001:db8::1]:443".parse().unwrap(),
+        relay_cert: TEST_CERT.repeat(2),
+        exit_pubkey: "4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs=".parse().unwrap(),
+    };
+    assert_eq!(TunnelConfig::Obfuscated(config.clone()).validate(), Ok(()));
+
+    config.client_ips_v6.clear();
+    config.dns.push("1.1.1.1".parse().unwrap());
+    config.relay_addr_v4 = "0.0.0.0:443".parse().unwrap();
+    config.relay_cert = "MAMCAQA=".into();
+    let err = TunnelConfig::Obfuscated(config).validate().unwrap_err();
+    assert_eq!(
+        err.0,
+        [
+            TunnelProblem::UnspecifiedEndpoint("0.0.0.0:443".parse().unwrap()),
+            TunnelProblem::NoIpv6Address,
+            TunnelProblem::DnsOutsideTunnel("1.1.1.1".parse().unwrap()),
+            TunnelProblem::InvalidRelayCert,
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid tunnel: endpoint 0.0.0.0:443 is unspecified, no IPv6 client address, DNS server 1.1.1.1 is outside the tunnel range, relay certificate is not a PEM certificate"
+    );
+
+    let tunnel: OneTunnel = serde_json::from_value(serde_json::json!({
+        "id": "dc799918-7738-446f-b1fc-ae3ba98103c7",
+        "status": { "type": "created", "when": 1725050273 },
+        "config": {
+            "type": "udp_port",
+            "client": { "wg_pubkey": "wjaiHUEOJ8k3X+U3b6H6yTcipqFipIbFQSB0CwZDNlQ=", "addresses": ["100.64.0.7/32"] },
+            "server": { "wg_pubkey": "4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs=", "endpoints": [], "dnses": ["100.64.0.1"] }
+        },
+        "relay": { "id": "NYC-001", "ip_v4": "8.8.31.3", "ip_v6": "::", "preferred_exits": [] },
+        "exit": { "id": "NYC-001", "country_code": "US", "city_code": "nyc", "city_name": "New York" }
+    }))
+    .unwrap();
+    assert_eq!(
+        tunnel.validate().unwrap_err().0,
+        [
+            TunnelProblem::NoEndpoints,
+            TunnelProblem::NoIpv6Address,
+            TunnelProblem::UnspecifiedRelayAddress("::".parse().unwrap()),
+        ]
+    );
+    assert_eq!(
+        TunnelConfig::Unknown(serde_json::json!({"type": "quic"})).validate().unwrap_err().0,
+        [TunnelProblem::UnsupportedTransport]
+    );
+}
@@ -1,92 +1,274 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fmt, net};
 
 use ipnetwork;
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
+use crate::country::CountryCode;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct AccountInfo {
-    pub id: String,
+    pub id: AccountId,
     pub active: bool,
     pub top_up: Option<TopUp>,
     pub subscription: Option<Subscription>,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl AccountInfo {
+    pub fn new(id: AccountId, active: bool, top_up: Option<TopUp>, subscription: Option<Subscription>) -> Self {
+        Self {
+            id,
+            active,
+            top_up,
+            subscription,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct TopUp {
-    pub credit_expires_at: i64,
+    pub credit_expires_at: Timestamp,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl TopUp {
+    pub fn new(credit_expires_at: Timestamp) -> Self {
+        Self {
+            credit_expires_at,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Subscription {
-    /// string repr of [`stripe::SubscriptionStatus`][https://docs.rs/async-stripe/latest/stripe/enum.SubscriptionStatus.html]
-    pub status: String,
-    /// period start in seconds since unix epoch
-    pub current_period_start: i64,
-    /// period end in seconds since unix epoch
-    pub current_period_end: i64,
+    pub status: SubscriptionStatus,
+    /// period start
+    pub current_period_start: Timestamp,
+    /// period end
+    pub current_period_end: Timestamp,
     /// whether the subscription will end at this period
     pub cancel_at_period_end: bool,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
 }
 
 impl Subscription {
-    pub fn new(status: String, current_period_start: i64, current_period_end: i64, cancel_at_period_end: bool) -> Self {
+    pub fn new(status: SubscriptionStatus, current_period_start: Timestamp, current_period_end: Timestamp, cancel_at_period_end: bool) -> Self {
         Self {
             status,
             current_period_start,
             current_period_end,
             cancel_at_period_end,
+            extra: UnknownFields::default(),
+        }
+    }
+}
+
+/// Mirrors [`stripe::SubscriptionStatus`](https://docs.rs/async-stripe/latest/stripe/enum.SubscriptionStatus.html).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionStatus {
+    Active,
+    Canceled,
+    Incomplete,
+    IncompleteExpired,
+    PastDue,
+    Paused,
+    Trialing,
+    Unpaid,
+
+    /// Catch-all for statuses this client doesn't know about.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl SubscriptionStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SubscriptionStatus::Active => "active",
+            SubscriptionStatus::Canceled => "canceled",
+            SubscriptionStatus::Incomplete => "incomplete",
+            SubscriptionStatus::IncompleteExpired => "incomplete_expired",
+            SubscriptionStatus::PastDue => "past_due",
+            SubscriptionStatus::Paused => "paused",
+            SubscriptionStatus::Trialing => "trialing",
+            SubscriptionStatus::Unpaid => "unpaid",
+            SubscriptionStatus::Other(status) => status,
         }
     }
+
+    /// Whether the subscription currently pays for the account.
+    ///
+    /// This includes trials and `past_due` subscriptions, for which Stripe keeps retrying the payment.
+    pub fn is_paying(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionStatus::Active | SubscriptionStatus::Trialing | SubscriptionStatus::PastDue
+        )
+    }
+
+    /// Whether the user has to update their payment method or confirm a payment.
+    pub fn needs_payment_action(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionStatus::Incomplete | SubscriptionStatus::PastDue | SubscriptionStatus::Unpaid
+        )
+    }
+
+    /// Whether the subscription has ended for good and can't become active again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, SubscriptionStatus::Canceled | SubscriptionStatus::IncompleteExpired)
+    }
+}
+
+impl Display for SubscriptionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[test]
+fn serde_subscription_status() {
+    let status: SubscriptionStatus = serde_json::from_str(r#""past_due""#).unwrap();
+    assert_eq!(status, SubscriptionStatus::PastDue);
+    assert!(status.is_paying() && status.needs_payment_action() && !status.is_terminal());
+    assert_eq!(
+        serde_json::to_string(&SubscriptionStatus::IncompleteExpired).unwrap(),
+        r#""incomplete_expired""#
+    );
+    assert!(SubscriptionStatus::IncompleteExpired.is_terminal());
+
+    let status: SubscriptionStatus = serde_json::from_str(r#""on_hold""#).unwrap();
+    assert_eq!(status, SubscriptionStatus::Other("on_hold".into()));
+    assert!(!status.is_paying() && !status.needs_payment_action() && !status.is_terminal());
+    assert_eq!(serde_json::to_string(&status).unwrap(), r#""on_hold""#);
+    assert_eq!(status.to_string(), "on_hold");
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct OneTunnel {
     pub id: String,
     pub status: TunnelStatus,
     pub config: TunnelConfig,
     pub relay: OneRelay,
     pub exit: OneExit,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl OneTunnel {
+    pub fn new(id: String, status: TunnelStatus, config: TunnelConfig, relay: OneRelay, exit: OneExit) -> Self {
+        Self {
+            id,
+            status,
+            config,
+            relay,
+            exit,
+            extra: UnknownFields::default(),
+        }
+    }
+
+    /// Whether this client understands the tunnel's transport.
+    ///
+    /// Unsupported tunnels can still be listed and deleted, but not connected to.
+    pub fn is_supported(&self) -> bool {
+        !self.config.is_unknown()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TunnelStatus {
     /// The tunnel has been created but not used yet.
     Created {
         /// Timestamp when this status was last updated.
-        when: i64,
+        when: Timestamp,
     },
     Connected {
         /// Timestamp when this status was last updated.
         ///
         /// Note: Not the timestamp when the tunnel was last connected to.
-        when: i64,
+        when: Timestamp,
     },
     Disconnected {
         /// Timestamp when this status was last updated.
         ///
         /// Note: This typically is the time when the tunnel was disconnected but that shouldn't be assumed.
-        when: i64,
+        when: Timestamp,
     },
+
+    /// A status this client doesn't know about, kept as raw JSON.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+impl TunnelStatus {
+    /// Timestamp when this status was last updated, if the status is known.
+    pub fn when(&self) -> Option<Timestamp> {
+        match self {
+            TunnelStatus::Created { when } | TunnelStatus::Connected { when } | TunnelStatus::Disconnected { when } => Some(*when),
+            TunnelStatus::Unknown(_) => None,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, TunnelStatus::Unknown(_))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TunnelConfig {
-    UdpPort { client: WgClientConfig, server: WgServerConfig },
+    UdpPort {
+        client: WgClientConfig,
+        server: WgServerConfig,
+    },
     Obfuscated(ObfuscatedTunnelConfig),
+
+    /// A transport this client doesn't know about, kept as raw JSON.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+impl TunnelConfig {
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, TunnelConfig::Unknown(_))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct WgClientConfig {
     pub wg_pubkey: WgPubkey,
+    #[cfg_attr(feature = "typescript", ts(as = "Vec<String>"))]
     pub addresses: Vec<ipnetwork::IpNetwork>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct WgServerConfig {
     pub wg_pubkey: WgPubkey,
     pub endpoints: Vec<net::SocketAddr>,
@@ -94,9 +276,12 @@ pub struct WgServerConfig {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct ObfuscatedTunnelConfig {
     pub client_pubkey: WgPubkey,
+    #[cfg_attr(feature = "typescript", ts(as = "Vec<String>"))]
     pub client_ips_v4: Vec<ipnetwork::Ipv4Network>,
+    #[cfg_attr(feature = "typescript", ts(as = "Vec<String>"))]
     pub client_ips_v6: Vec<ipnetwork::Ipv6Network>,
     pub dns: Vec<net::IpAddr>,
     pub relay_addr_v4: net::SocketAddrV4,
@@ -105,9 +290,10 @@ pub struct ObfuscatedTunnelConfig {
     pub exit_pubkey: WgPubkey,
 }
 
-const WG_PUBKEY_LENGTH: usize = 32;
+const WG_KEY_LENGTH: usize = 32;
 #[derive(Deserialize, Clone, Eq, PartialEq)]
-pub struct WgPubkey(#[serde(deserialize_with = "deserialize_base64")] pub [u8; WG_PUBKEY_LENGTH]);
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(type = "string"))]
+pub struct WgPubkey(#[serde(deserialize_with = "deserialize_base64")] pub [u8; WG_KEY_LENGTH]);
 
 impl std::fmt::Debug for WgPubkey {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -116,14 +302,18 @@ impl std::fmt::Debug for WgPubkey {
     }
 }
 
+/// An error parsing a base64 encoded WireGuard key.
 #[derive(Error, Debug)]
-pub enum ParseWgPubkeyError {
-    #[error("expected {} bytes, found {}", WG_PUBKEY_LENGTH, .0)]
+pub enum ParseWgKeyError {
+    #[error("expected {} bytes, found {}", WG_KEY_LENGTH, .0)]
     InvalidLength(usize),
     #[error("base64 decode err: {:?}", .0)]
     NotBase64(#[from] base64::DecodeError),
 }
 
+/// The error of parsing a [`WgPubkey`], which is the same as for other keys.
+pub type ParseWgPubkeyError = ParseWgKeyError;
+
 impl FromStr for WgPubkey {
     type Err = ParseWgPubkeyError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -150,7 +340,7 @@ impl Serialize for WgPubkey {
     }
 }
 
-fn deserialize_base64<'de, D>(deserializer: D) -> Result<[u8; WG_PUBKEY_LENGTH], D::Error>
+fn deserialize_base64<'de, D>(deserializer: D) -> Result<[u8; WG_KEY_LENGTH], D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -158,35 +348,230 @@ where
     match WgPubkey::from_str(&encoded) {
         Ok(pk) => Ok(pk.0),
         Err(ParseWgPubkeyError::InvalidLength(n)) => {
-            let error_string = format!("a base64 string representing {} bytes", WG_PUBKEY_LENGTH);
+            let error_string = format!("a base64 string representing {} bytes", WG_KEY_LENGTH);
             Err(D::Error::invalid_length(n, &error_string.as_str()))
         }
         Err(ParseWgPubkeyError::NotBase64(err)) => Err(D::Error::custom(err)),
     }
 }
 
+/// A WireGuard private key.
+///
+/// The key is zeroized on drop and redacted in `Debug` output. `Display` and [`FromStr`] use base64, like WireGuard configs.
+#[cfg(feature = "keys")]
+#[derive(Clone)]
+pub struct WgSecretKey(x25519_dalek::StaticSecret);
+
+#[cfg(feature = "keys")]
+impl WgSecretKey {
+    /// Generates a new key from the operating system's random number generator.
+    pub fn generate() -> Self {
+        Self(x25519_dalek::StaticSecret::random())
+    }
+
+    pub fn from_bytes(bytes: [u8; WG_KEY_LENGTH]) -> Self {
+        let bytes = zeroize::Zeroizing::new(bytes);
+        Self(x25519_dalek::StaticSecret::from(*bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; WG_KEY_LENGTH] {
+        self.0.as_bytes()
+    }
+
+    pub fn pubkey(&self) -> WgPubkey {
+        WgPubkey(x25519_dalek::PublicKey::from(&self.0).to_bytes())
+    }
+}
+
+#[cfg(feature = "keys")]
+impl Debug for WgSecretKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WgSecretKey").field(&"<redacted>").finish()
+    }
+}
+
+#[cfg(feature = "keys")]
+impl FromStr for WgSecretKey {
+    type Err = ParseWgKeyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        let decoded = zeroize::Zeroizing::new(STANDARD.decode(s)?);
+        let bytes: [u8; WG_KEY_LENGTH] = decoded[..].try_into().map_err(|_| ParseWgKeyError::InvalidLength(decoded.len()))?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "keys")]
+impl Display for WgSecretKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+        Base64Display::new(self.as_bytes(), &STANDARD).fmt(f)
+    }
+}
+
+/// A WireGuard preshared key, which mixes an additional symmetric secret into the handshake.
+///
+/// Like [`WgSecretKey`], the key is zeroized on drop and redacted in `Debug` output.
+#[cfg(feature = "keys")]
+#[derive(Clone, PartialEq, Eq)]
+pub struct WgPresharedKey(zeroize::Zeroizing<[u8; WG_KEY_LENGTH]>);
+
+#[cfg(feature = "keys")]
+impl WgPresharedKey {
+    pub fn generate() -> Self {
+        use rand_core::RngCore;
+        let mut bytes = zeroize::Zeroizing::new([0; WG_KEY_LENGTH]);
+        rand_core::OsRng.fill_bytes(&mut bytes[..]);
+        Self(bytes)
+    }
+
+    pub fn from_bytes(bytes: [u8; WG_KEY_LENGTH]) -> Self {
+        Self(zeroize::Zeroizing::new(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; WG_KEY_LENGTH] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "keys")]
+impl Debug for WgPresharedKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WgPresharedKey").field(&"<redacted>").finish()
+    }
+}
+
+#[cfg(feature = "keys")]
+impl FromStr for WgPresharedKey {
+    type Err = ParseWgKeyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        let decoded = zeroize::Zeroizing::new(STANDARD.decode(s)?);
+        let bytes: [u8; WG_KEY_LENGTH] = decoded[..].try_into().map_err(|_| ParseWgKeyError::InvalidLength(decoded.len()))?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "keys")]
+impl Display for WgPresharedKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+        Base64Display::new(self.as_bytes(), &STANDARD).fmt(f)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct OneRelay {
     pub id: String,
     pub ip_v4: net::Ipv4Addr,
     pub ip_v6: net::Ipv6Addr,
     pub preferred_exits: Vec<RelayPreferredExit>,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl OneRelay {
+    pub fn new(id: String, ip_v4: net::Ipv4Addr, ip_v6: net::Ipv6Addr, preferred_exits: Vec<RelayPreferredExit>) -> Self {
+        Self {
+            id,
+            ip_v4,
+            ip_v6,
+            preferred_exits,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct RelayPreferredExit {
     pub id: String,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl RelayPreferredExit {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct OneExit {
     pub id: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
     pub city_code: String,
     pub city_name: String,
+    /// Location of the exit, if the server provides it. See [`OneExit::location`] for a fallback.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub coordinates: Option<Coordinates>,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl OneExit {
+    /// An exit without [`coordinates`](OneExit::coordinates).
+    pub fn new(id: String, country_code: CountryCode, city_code: String, city_name: String) -> Self {
+        Self {
+            id,
+            country_code,
+            city_code,
+            city_name,
+            coordinates: None,
+            extra: UnknownFields::default(),
+        }
+    }
+}
+
+/// A position on Earth in decimal degrees.
+///
+/// Compared by bit pattern, so that it can be part of types implementing [`Eq`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self { latitude, longitude }
+    }
+
+    /// Great-circle distance in kilometers, using the haversine formula.
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * Self::EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
 }
 
+impl PartialEq for Coordinates {
+    fn eq(&self, other: &Self) -> bool {
+        self.latitude.to_bits() == other.latitude.to_bits() && self.longitude.to_bits() == other.longitude.to_bits()
+    }
+}
+
+impl Eq for Coordinates {}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Prices {
     pub subscription: Vec<Price>,
     pub top_up: Vec<Price>,
@@ -195,9 +580,25 @@ pub struct Prices {
     ///
     /// The individual prices may also have specific reasons (which may be the same).
     pub sale: Option<Sale>,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl Prices {
+    pub fn new(subscription: Vec<Price>, top_up: Vec<Price>, sale: Option<Sale>) -> Self {
+        Self {
+            subscription,
+            top_up,
+            sale,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Price {
     pub months: u16,
     pub usd_cents: u32,
@@ -207,15 +608,46 @@ pub struct Price {
     pub regular_usd_cents: u32,
 
     pub sale: Option<Sale>,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl Price {
+    pub fn new(months: u16, usd_cents: u32, regular_usd_cents: u32, sale: Option<Sale>) -> Self {
+        Self {
+            months,
+            usd_cents,
+            regular_usd_cents,
+            sale,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Sale {
     /// Example: "Launch Sale"
     pub title: String,
 
     /// Example: "Thanks for being an early Obscura user. Sign up now and get a special price."
     pub summary: String,
+
+    #[serde(flatten)]
+    #[cfg_attr(feature = "typescript", ts(skip))]
+    extra: UnknownFields,
+}
+
+impl Sale {
+    pub fn new(title: String, summary: String) -> Self {
+        Self {
+            title,
+            summary,
+            extra: UnknownFields::default(),
+        }
+    }
 }
 
 #[test]
@@ -238,6 +670,375 @@ fn serde_wg_enc_dec() {
     assert_eq!(base64_from_pk, base64);
 }
 
+#[cfg(feature = "keys")]
+#[test]
+fn wg_secret_key() {
+    // RFC 7748 section 6.1
+    let sk: WgSecretKey = "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo=".parse().unwrap();
+    assert_eq!(sk.to_string(), "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo=");
+    assert_eq!(sk.pubkey().to_string(), "hSDwCYkwp1R0i33ctD73Wg2/Og0mOBr066SpjqqbTmo=");
+    assert_eq!(format!("{sk:?}"), r#"WgSecretKey("<redacted>")"#);
+    assert!(matches!("AAAA".parse::<WgSecretKey>(), Err(ParseWgKeyError::InvalidLength(3))));
+
+    let generated = WgSecretKey::generate();
+    assert_ne!(generated.as_bytes(), sk.as_bytes());
+    assert_eq!(generated.to_string().parse::<WgSecretKey>().unwrap().pubkey(), generated.pubkey());
+}
+
+/// Number of digits in an account ID, including the trailing Verhoeff check digit.
+pub const ACCOUNT_ID_LENGTH: usize = 20;
+
+/// An account number, which is also the only credential needed to log in.
+///
+/// Parsing accepts user input with spaces or dashes between digits and checks the format, including the check digit with the `keys` feature. Deserialization takes the server's string as is, use [`AccountId::validate`] to check it. `Display` groups the digits in fours for readability, while [`AccountId::as_str`] and serialization use the plain digits. `Debug` output is redacted.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(type = "string"))]
+pub struct AccountId(String);
+
+impl AccountId {
+    /// Generates a new random account ID.
+    #[cfg(feature = "keys")]
+    pub fn generate() -> Self {
+        use rand_core::RngCore as _;
+        use verhoeff::VerhoeffMut as _;
+        let mut digits = format!("{:019}", rand_core::OsRng.next_u64() >> 1);
+        digits.push_verhoeff_check_digit();
+        Self(digits)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Checks that the ID has the expected length and, with the `keys` feature, check digit.
+    pub fn validate(&self) -> Result<(), ParseAccountIdError> {
+        if let Some(c) = self.0.chars().find(|c| !c.is_ascii_digit()) {
+            return Err(ParseAccountIdError::InvalidCharacter(c));
+        }
+        if self.0.len() != ACCOUNT_ID_LENGTH {
+            return Err(ParseAccountIdError::InvalidLength(self.0.len()));
+        }
+        #[cfg(feature = "keys")]
+        if !verhoeff::Verhoeff::validate_verhoeff_check_digit(self.0.as_str()) {
+            return Err(ParseAccountIdError::InvalidCheckDigit);
+        }
+        Ok(())
+    }
+}
+
+impl Debug for AccountId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AccountId").field(&"<redacted>").finish()
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseAccountIdError {
+    #[error("unexpected character {:?}", .0)]
+    InvalidCharacter(char),
+    #[error("expected {} digits, found {}", ACCOUNT_ID_LENGTH, .0)]
+    InvalidLength(usize),
+    #[error("invalid check digit")]
+    InvalidCheckDigit,
+}
+
+impl FromStr for AccountId {
+    type Err = ParseAccountIdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = Self(s.chars().filter(|c| !c.is_whitespace() && *c != '-').collect());
+        id.validate()?;
+        Ok(id)
+    }
+}
+
+impl Display for AccountId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.0.chars().enumerate() {
+            if i > 0 && i % 4 == 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for AccountId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[test]
+fn account_id() {
+    let id: AccountId = " 1234-5678 9012 3456 7895\n".parse().unwrap();
+    assert_eq!(id.as_str(), "12345678901234567895");
+    assert_eq!(id.to_string(), "1234 5678 9012 3456 7895");
+    assert_eq!(format!("{id:?}"), r#"AccountId("<redacted>")"#);
+    assert_eq!(serde_json::to_string(&id).unwrap(), r#""12345678901234567895""#);
+    assert_eq!(serde_json::from_str::<AccountId>(r#""12345678901234567895""#).unwrap(), id);
+
+    assert_eq!("1234567890123456789".parse::<AccountId>(), Err(ParseAccountIdError::InvalidLength(19)));
+    assert_eq!(
+        "1234567890123456789O".parse::<AccountId>(),
+        Err(ParseAccountIdError::InvalidCharacter('O'))
+    );
+    let unchecked: AccountId = serde_json::from_str(r#""0000""#).unwrap();
+    assert_eq!(unchecked.as_str(), "0000");
+    assert_eq!(unchecked.validate(), Err(ParseAccountIdError::InvalidLength(4)));
+}
+
+#[cfg(feature = "keys")]
+#[test]
+fn account_id_check_digit() {
+    assert_eq!("12345678901234567894".parse::<AccountId>(), Err(ParseAccountIdError::InvalidCheckDigit));
+
+    let generated = AccountId::generate();
+    assert_eq!(generated.as_str().parse::<AccountId>().unwrap(), generated);
+}
+
+/// Implements the accessors of the `extra` field of API objects.
+macro_rules! unknown_fields {
+    ($($ty:ty),* $(,)?) => {$(
+        impl $ty {
+            /// Fields sent by the server which this crate doesn't know about.
+            pub fn extra(&self) -> &UnknownFields {
+                &self.extra
+            }
+
+            pub fn extra_mut(&mut self) -> &mut UnknownFields {
+                &mut self.extra
+            }
+        }
+    )*};
+}
+pub(crate) use unknown_fields;
+
+unknown_fields!(
+    AccountInfo,
+    TopUp,
+    Subscription,
+    OneTunnel,
+    OneRelay,
+    RelayPreferredExit,
+    OneExit,
+    Prices,
+    Price,
+    Sale
+);
+
+/// Fields of an API object which this crate doesn't know about.
+///
+/// They are only retained with the `unknown-fields` feature, which allows re-serializing objects without losing data sent by newer servers. Otherwise this is always empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnknownFields(serde_json::Map<String, serde_json::Value>);
+
+impl UnknownFields {
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.0.get(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_map(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.0
+    }
+
+    pub fn as_map_mut(&mut self) -> &mut serde_json::Map<String, serde_json::Value> {
+        &mut self.0
+    }
+}
+
+impl Serialize for UnknownFields {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[cfg(feature = "unknown-fields")]
+        return serde_json::Map::deserialize(deserializer).map(Self);
+        #[cfg(not(feature = "unknown-fields"))]
+        {
+            serde::de::IgnoredAny::deserialize(deserializer)?;
+            Ok(Self::default())
+        }
+    }
+}
+
+/// A point in time with second precision, sent as seconds since unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS), ts(type = "number"))]
+#[serde(transparent)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    pub const fn from_unix_secs(secs: i64) -> Self {
+        Self(secs)
+    }
+
+    pub const fn unix_secs(self) -> i64 {
+        self.0
+    }
+
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// The timestamp as a `SystemTime`, or `None` if it is out of the platform's range.
+    pub fn to_system_time(self) -> Option<SystemTime> {
+        let offset = Duration::from_secs(self.0.unsigned_abs());
+        if self.0 >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        }
+    }
+
+    /// Time left until this timestamp, or zero if it has passed.
+    pub fn duration_until(self, now: SystemTime) -> Duration {
+        match self.offset_from(now) {
+            (until, true) => until,
+            (_, false) => Duration::ZERO,
+        }
+    }
+
+    /// Time passed since this timestamp, or zero if it is in the future.
+    pub fn duration_since(self, now: SystemTime) -> Duration {
+        match self.offset_from(now) {
+            (_, true) => Duration::ZERO,
+            (since, false) => since,
+        }
+    }
+
+    /// Distance from `now`, and whether this timestamp is in the future. Timestamps out of the platform's range are treated as infinitely far away.
+    fn offset_from(self, now: SystemTime) -> (Duration, bool) {
+        match self.to_system_time() {
+            Some(time) => match time.duration_since(now) {
+                Ok(until) => (until, true),
+                Err(err) => (err.duration(), false),
+            },
+            None => (Duration::MAX, self.0 > 0),
+        }
+    }
+
+    /// Formats the timestamp relative to `now`, for example "in 3 days" or "5 minutes ago".
+    pub fn format_relative(self, now: SystemTime) -> String {
+        const UNITS: [(u64, &str); 5] = [
+            (365 * 86400, "year"),
+            (30 * 86400, "month"),
+            (86400, "day"),
+            (3600, "hour"),
+            (60, "minute"),
+        ];
+        let (offset, future) = self.offset_from(now);
+        let secs = offset.as_secs();
+        let Some((count, unit)) = UNITS
+            .iter()
+            .find(|(unit_secs, _)| secs >= *unit_secs)
+            .map(|(unit_secs, unit)| (secs / unit_secs, unit))
+        else {
+            return "just now".to_string();
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        if future {
+            format!("in {count} {unit}{plural}")
+        } else {
+            format!("{count} {unit}{plural} ago")
+        }
+    }
+}
+
+/// Rounds down to whole seconds, also before the epoch.
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Self(since.as_secs().try_into().unwrap_or(i64::MAX)),
+            Err(err) => {
+                let before = err.duration();
+                let secs = before.as_secs() + u64::from(before.subsec_nanos() > 0);
+                Self(0i64.saturating_sub_unsigned(secs))
+            }
+        }
+    }
+}
+
+/// Formats as an RFC 3339 date and time in UTC, for example "2024-08-30T20:37:53Z".
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Days to civil date conversion from http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = self.0.div_euclid(86400);
+        let secs = self.0.rem_euclid(86400);
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60
+        )
+    }
+}
+
+#[test]
+fn test_timestamp() {
+    let timestamp: Timestamp = serde_json::from_str("1725050273").unwrap();
+    assert_eq!(timestamp, Timestamp::from_unix_secs(1725050273));
+    assert_eq!(serde_json::to_string(&timestamp).unwrap(), "1725050273");
+    assert_eq!(timestamp.to_string(), "2024-08-30T20:37:53Z");
+    assert_eq!(Timestamp::from_unix_secs(0).to_string(), "1970-01-01T00:00:00Z");
+    assert_eq!(Timestamp::from_unix_secs(-1).to_string(), "1969-12-31T23:59:59Z");
+    assert_eq!(Timestamp::from_unix_secs(951782400).to_string(), "2000-02-29T00:00:00Z");
+
+    let system_time = timestamp.to_system_time().unwrap();
+    assert_eq!(Timestamp::from(system_time), timestamp);
+    assert_eq!(
+        Timestamp::from(Timestamp::from_unix_secs(-100).to_system_time().unwrap()),
+        Timestamp::from_unix_secs(-100)
+    );
+    assert_eq!(Timestamp::from(UNIX_EPOCH - Duration::from_millis(500)), Timestamp::from_unix_secs(-1));
+    assert_eq!(Timestamp::from(UNIX_EPOCH + Duration::from_millis(500)), Timestamp::from_unix_secs(0));
+
+    // Representable on some platforms only, but never a panic.
+    let (distant_past, distant_future) = (Timestamp::from_unix_secs(i64::MIN), Timestamp::from_unix_secs(i64::MAX));
+    assert_eq!(distant_past.duration_until(system_time), Duration::ZERO);
+    assert!(distant_past.format_relative(system_time).ends_with(" years ago"));
+    assert_eq!(distant_future.duration_since(system_time), Duration::ZERO);
+    assert!(distant_future.format_relative(system_time).starts_with("in "));
+
+    let now = system_time + Duration::from_secs(90);
+    assert_eq!(timestamp.duration_since(now), Duration::from_secs(90));
+    assert_eq!(timestamp.duration_until(now), Duration::ZERO);
+    assert_eq!(timestamp.format_relative(now), "1 minute ago");
+    assert_eq!(timestamp.format_relative(system_time), "just now");
+    assert_eq!(timestamp.format_relative(system_time - Duration::from_secs(3 * 86400 + 5)), "in 3 days");
+    assert_eq!(timestamp.format_relative(system_time + Duration::from_secs(2 * 3600)), "2 hours ago");
+    assert_eq!(timestamp.format_relative(system_time - Duration::from_secs(400 * 86400)), "in 1 year");
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct AuthToken(String);
 
